@@ -1,10 +1,13 @@
 use std::fs;
 
-use crate::visual::canvas::Canvas;
+use crate::visual::{canvas::Canvas, color::Col};
 
 /// Maximum Color Value .Must be less than 65536 and more than zero.
 pub const COLOR_MAXVAL: u16 = 255;
 
+/// biggest image we read (an 8k panorama), so a broken header cant make us allocate gigabytes
+pub const MAX_PPM_PIXELS: usize = 8192 * 4096;
+
 impl Canvas {
     /// converts to a .ppm string
     pub fn canvas_to_ppm(&self) -> String {
@@ -39,6 +42,98 @@ impl Canvas {
     }
 }
 
+impl Canvas {
+    /// reads a plain (P3) or binary (P6) ppm file, like one we wrote or an image texture.
+    /// Errors say what is wrong with the file.
+    pub fn from_ppm(bytes: &[u8]) -> Result<Canvas, String> {
+        let mut reader = PpmReader { bytes, pos: 0 };
+        let magic = reader.token()?;
+        let width = reader.number()?;
+        let height = reader.number()?;
+        let maxval = reader.number()?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(format!("invalid maximum color value {maxval}"));
+        }
+        let samples_count = width
+            .checked_mul(height)
+            .filter(|pixels| *pixels <= MAX_PPM_PIXELS)
+            .ok_or_else(|| {
+                format!("image of {width} x {height} is too big, at most {MAX_PPM_PIXELS} pixels")
+            })?
+            * 3;
+
+        let samples: Vec<usize> = match magic.as_str() {
+            "P3" => (0..samples_count)
+                .map(|_| reader.number())
+                .collect::<Result<_, _>>()?,
+            "P6" => {
+                // exactly one whitespace between the header and the binary data
+                let data = bytes.get(reader.pos + 1..).unwrap_or_default();
+                let size = if maxval < 256 { 1 } else { 2 };
+                if data.len() < samples_count * size {
+                    return Err("not enough pixels".to_string());
+                }
+                data.chunks(size)
+                    .take(samples_count)
+                    .map(|sample| {
+                        sample
+                            .iter()
+                            .fold(0, |value, byte| value * 256 + *byte as usize)
+                    })
+                    .collect()
+            }
+            _ => return Err(format!("expected P3 or P6, not '{magic}'")),
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        let scale = maxval as f64;
+        for (i, rgb) in samples.chunks(3).enumerate() {
+            let col = Col::new(
+                rgb[0] as f64 / scale,
+                rgb[1] as f64 / scale,
+                rgb[2] as f64 / scale,
+            );
+            canvas.write_px(i % width, i / width, col);
+        }
+        Ok(canvas)
+    }
+}
+
+/// splits the (ascii part of the) ppm into whitespace separated tokens, skipping comments
+struct PpmReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl PpmReader<'_> {
+    fn token(&mut self) -> Result<String, String> {
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(byte) if byte.is_ascii_whitespace() => self.pos += 1,
+                Some(b'#') => {
+                    while !matches!(self.bytes.get(self.pos), None | Some(b'\n')) {
+                        self.pos += 1;
+                    }
+                }
+                Some(_) => break,
+                None => return Err("unexpected end of file".to_string()),
+            }
+        }
+        let start = self.pos;
+        while matches!(self.bytes.get(self.pos), Some(byte) if !byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        Ok(String::from_utf8_lossy(&self.bytes[start..self.pos]).to_string())
+    }
+
+    fn number(&mut self) -> Result<usize, String> {
+        let token = self.token()?;
+        token
+            .parse()
+            .map_err(|_| format!("expected a number, not '{token}'"))
+    }
+}
+
 pub fn write_to_file(path: &str, data: String) {
     fs::write(path, data).expect("unable to write file");
 }
@@ -93,4 +188,61 @@ mod tests {
         let exp = l1.to_owned() + l2 + l3 + l4;
         assert_eq!(exp, result);
     }
+
+    #[test]
+    fn reading_a_ppm() {
+        let ppm = "P3\n10 2\n255\n".to_string()
+            + "0 0 0  1 1 1  2 2 2  3 3 3  4 4 4  5 5 5  6 6 6  7 7 7  8 8 8  9 9 9\n"
+            + "0 0 0  1 1 1  2 2 2  3 3 3  4 4 4  5 5 5  6 6 6  7 7 7  8 8 8  9 9 9\n";
+        let canvas = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+        assert_eq!((canvas.width, canvas.height), (10, 2));
+
+        let ppm = "P3\n# this is a comment\n2 1\n# this, too\n255\n# another comment\n255 255 255\n# oh, no, comments in the pixel data!\n255 0 255\n";
+        let canvas = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+        assert_eq!(*canvas.read(0, 0), WHITE);
+        assert_eq!(*canvas.read(1, 0), Col::new(1., 0., 1.));
+
+        // colors are scaled by the maximum value
+        let canvas =
+            Canvas::from_ppm(b"P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n").unwrap();
+        assert_eq!(*canvas.read(0, 1), Col::new(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn reading_what_we_wrote() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.write_px(0, 0, Col::new(1.0, 0.0, 0.0));
+        canvas.write_px(2, 1, Col::new(0.0, 0.2, 1.0));
+        let read = Canvas::from_ppm(canvas.canvas_to_ppm().as_bytes()).unwrap();
+        assert_eq!(*read.read(0, 0), Col::new(1.0, 0.0, 0.0));
+        assert_eq!(*read.read(2, 1), Col::new(0.0, 51. / 255., 1.0));
+    }
+
+    #[test]
+    fn reading_a_binary_ppm() {
+        let mut ppm = b"P6\n# binary\n2 1\n255\n".to_vec();
+        ppm.extend([255, 0, 0, 0, 51, 255]);
+        let canvas = Canvas::from_ppm(&ppm).unwrap();
+        assert_eq!(*canvas.read(0, 0), Col::new(1.0, 0.0, 0.0));
+        assert_eq!(*canvas.read(1, 0), Col::new(0.0, 0.2, 1.0));
+    }
+
+    #[test]
+    fn reading_a_broken_ppm() {
+        assert!(Canvas::from_ppm(b"P32\n1 1\n255\n0 0 0\n").is_err());
+        assert!(Canvas::from_ppm(b"P3\n2 1\n255\n0 0 0\n").is_err());
+        assert!(Canvas::from_ppm(b"P3\n1 1\n0\n0 0 0\n").is_err());
+        assert!(Canvas::from_ppm(b"P6\n2 1\n255\n\x01\x02").is_err());
+    }
+
+    #[test]
+    fn reading_a_ppm_with_an_oversized_header() {
+        // width * height * 3 overflows usize
+        let huge = format!("P3\n{} {}\n255\n0 0 0\n", usize::MAX / 2, 3);
+        assert!(Canvas::from_ppm(huge.as_bytes()).is_err());
+        // fits into usize, but way more pixels than any texture
+        assert!(Canvas::from_ppm(b"P6\n100000 100000\n255\n\x01\x02\x03").is_err());
+        let err = Canvas::from_ppm(b"P3\n100000 100000\n255\n0 0 0\n").unwrap_err();
+        assert!(err.contains("too big"));
+    }
 }
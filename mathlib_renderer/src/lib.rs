@@ -1,3 +1,17 @@
+// the unit tests follow the book closely and are kept as they were written,
+// so the lints newer clippy versions have for them are turned off there.
+#![cfg_attr(
+    test,
+    allow(
+        clippy::approx_constant,
+        clippy::bool_assert_comparison,
+        clippy::init_numbered_fields,
+        clippy::needless_borrow,
+        clippy::nonminimal_bool,
+        clippy::unnecessary_cast
+    )
+)]
+
 pub mod cmp;
 pub mod io {
     pub mod ppm;
@@ -15,6 +29,9 @@ pub mod ray;
 
 pub mod object;
 
+pub mod noise;
+pub mod random;
+
 pub mod visual {
     pub mod camera;
     pub mod canvas;
@@ -22,5 +39,7 @@ pub mod visual {
     pub mod light;
     pub mod material;
     pub mod patterns;
+    pub mod render;
+    pub mod uv;
     pub mod world;
 }
@@ -107,7 +107,7 @@ impl Matrix3 {
     /// minors that change their sign if row+col is odd
     pub fn cofactor(&self, row: usize, col: usize) -> f64 {
         let res = self.minor(row, col);
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             return res;
         }
         -res
@@ -175,7 +175,7 @@ impl Matrix {
     /// minors that change their sign if row+col is odd
     pub fn cofactor(&self, row: usize, col: usize) -> f64 {
         let res = self.minor(row, col);
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             return res;
         }
         -res
@@ -186,9 +186,14 @@ impl Matrix {
         (0..4).fold(0.0, |acc, x| acc + self[0][x] * self.cofactor(0, x))
     }
 
+    /// false for matrices that loose information (like scaling by 0), inverse panics for those
+    pub fn is_invertible(&self) -> bool {
+        !self.determinant().apx_eq(&0.0)
+    }
+
     /// inverts the effect or reversing multiplication of a matrix
     pub fn inverse(&self) -> Self {
-        if self.determinant().apx_eq(&0.0) {
+        if !self.is_invertible() {
             panic!("Could not find inverse of {self:?}");
             // since we never handle this case anyway this should be more conveniant.
         }
@@ -480,6 +485,8 @@ mod tests {
             [0.0, 0.0, 0.0, 0.0],
         ]);
         assert_eq!(not_invertible.determinant(), 0.0);
+        assert!(!not_invertible.is_invertible());
+        assert!(Matrix::new_identity().is_invertible());
         not_invertible.inverse(); // -> Panics
     }
 
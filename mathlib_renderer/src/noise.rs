@@ -0,0 +1,252 @@
+use std::{
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{mathstructs::point::Point, random::Rng};
+
+/// Ken Perlin's (improved) gradient noise. Smooth "random" values that slowly change when moving
+/// the point around.
+/// - roughly between -1 and 1, always 0 on whole numbers
+/// - the same point always gives the same value
+pub fn perlin(x: f64, y: f64, z: f64) -> f64 {
+    static DEFAULT: OnceLock<Noise> = OnceLock::new();
+    DEFAULT.get_or_init(|| Noise::new(0x5EED)).at(x, y, z)
+}
+
+/// perlin noise, but every seed gives different (still deterministic) noise
+#[derive(Clone)]
+pub struct Noise {
+    seed: u64,
+    /// shared, so cloning patterns stays cheap
+    permutation: Arc<[usize; 512]>,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            permutation: Arc::new(permutation(seed)),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn at(&self, x: f64, y: f64, z: f64) -> f64 {
+        let p = &self.permutation;
+        // the unit cube the point is in (wrapped around after 256)...
+        let (xi, yi, zi) = (cell(x), cell(y), cell(z));
+        // ...and where inside of it
+        let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        // hashes of the 8 corners of the cube
+        let a = p[xi] + yi;
+        let (aa, ab) = (p[a] + zi, p[a + 1] + zi);
+        let b = p[xi + 1] + yi;
+        let (ba, bb) = (p[b] + zi, p[b + 1] + zi);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1., y, z)),
+                lerp(u, grad(p[ab], x, y - 1., z), grad(p[bb], x - 1., y - 1., z)),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.),
+                    grad(p[ba + 1], x - 1., y, z - 1.),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1., z - 1.),
+                    grad(p[bb + 1], x - 1., y - 1., z - 1.),
+                ),
+            ),
+        )
+    }
+}
+
+// the permutation follows from the seed, no need to compare or print it
+impl PartialEq for Noise {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+}
+
+impl fmt::Debug for Noise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Noise").field("seed", &self.seed).finish()
+    }
+}
+
+/// noise summed up over octaves. Every octave has double the frequency and half the strength
+/// of the one before, which adds finer and finer detail.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fractal {
+    pub noise: Noise,
+    pub octaves: u32,
+    /// of the first octave. Higher means smaller features.
+    pub frequency: f64,
+}
+
+impl Fractal {
+    pub fn new(seed: u64, octaves: u32, frequency: f64) -> Self {
+        Self {
+            noise: Noise::new(seed),
+            octaves,
+            frequency,
+        }
+    }
+
+    /// fractal brownian motion. Soft, roughly between -1 and 1, like clouds.
+    pub fn fbm(&self, point: &Point) -> f64 {
+        self.sum(point, |noise| noise)
+    }
+
+    /// sum of the absolute noise. Between 0 and ~1, with sharp creases where the noise is 0.
+    pub fn turbulence(&self, point: &Point) -> f64 {
+        self.sum(point, f64::abs)
+    }
+
+    fn sum(&self, point: &Point, shape: impl Fn(f64) -> f64) -> f64 {
+        let mut sum = 0.;
+        let mut frequency = self.frequency;
+        let mut amplitude = 0.5;
+        for _ in 0..self.octaves {
+            let noise = self.noise.at(
+                point.x * frequency,
+                point.y * frequency,
+                point.z * frequency,
+            );
+            sum += shape(noise) * amplitude;
+            frequency *= 2.;
+            amplitude *= 0.5;
+        }
+        // the amplitudes add up to almost 1, this makes them exactly 1
+        match self.octaves {
+            0 => 0.,
+            octaves => sum / (1. - 0.5_f64.powi(octaves as i32)),
+        }
+    }
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Self::new(0, 4, 1.)
+    }
+}
+
+/// the numbers 0..256 shuffled (and repeated once, so we dont have to wrap the indices).
+/// The same seed always gives the same shuffle, so the noise never changes between runs.
+fn permutation(seed: u64) -> [usize; 512] {
+    let mut shuffled: Vec<usize> = (0..256).collect();
+    let mut rng = Rng::new(seed);
+    for i in (1..256).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        shuffled.swap(i, j);
+    }
+    let mut p = [0; 512];
+    for (i, value) in p.iter_mut().enumerate() {
+        *value = shuffled[i % 256];
+    }
+    p
+}
+
+fn cell(v: f64) -> usize {
+    (v.floor() as i64 & 255) as usize
+}
+
+/// smooths the position inside the cell: 6t^5 - 15t^4 + 10t^3
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// dot product of (x, y, z) with one of 12 gradient directions picked by the hash
+fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    let u = if h & 1 == 0 { u } else { -u };
+    let v = if h & 2 == 0 { v } else { -v };
+    u + v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_zero_on_whole_numbers() {
+        for (x, y, z) in [(0., 0., 0.), (1., 2., 3.), (-4., 7., -1.), (300., -2., 5.)] {
+            assert_eq!(perlin(x, y, z), 0.);
+        }
+    }
+
+    #[test]
+    fn noise_is_smooth_and_stays_in_range() {
+        let mut rng = Rng::new(7);
+        let mut values = vec![];
+        for _ in 0..1000 {
+            let (x, y, z) = (
+                rng.next_f64() * 20. - 10.,
+                rng.next_f64() * 20. - 10.,
+                rng.next_f64() * 20. - 10.,
+            );
+            let value = perlin(x, y, z);
+            assert!((-1.0..=1.0).contains(&value), "{value}");
+            // moving a tiny bit only changes the value a tiny bit
+            assert!((perlin(x + 0.001, y, z) - value).abs() < 0.01);
+            assert_eq!(perlin(x, y, z), value);
+            values.push(value);
+        }
+        // not just the same value everywhere
+        assert!(values.iter().any(|v| *v > 0.2));
+        assert!(values.iter().any(|v| *v < -0.2));
+    }
+
+    #[test]
+    fn seeds_give_different_noise() {
+        let (a, b) = (Noise::new(1), Noise::new(2));
+        assert_eq!(a.at(0.3, 0.7, 1.1), Noise::new(1).at(0.3, 0.7, 1.1));
+        assert_ne!(a.at(0.3, 0.7, 1.1), b.at(0.3, 0.7, 1.1));
+        assert_eq!(a, Noise::new(1));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn octaves_add_detail() {
+        let point = Point::new(0.3, 0.7, 1.1);
+        let one = Fractal::new(3, 1, 1.);
+        // one octave is just the noise
+        assert_eq!(one.fbm(&point), one.noise.at(0.3, 0.7, 1.1));
+        assert_eq!(one.turbulence(&point), one.noise.at(0.3, 0.7, 1.1).abs());
+        let four = Fractal::new(3, 4, 1.);
+        assert_ne!(four.fbm(&point), one.fbm(&point));
+        assert_eq!(Fractal::new(3, 0, 1.).fbm(&point), 0.);
+
+        let mut rng = Rng::new(11);
+        for _ in 0..500 {
+            let point = Point::new(
+                rng.next_f64() * 10.,
+                rng.next_f64() * 10.,
+                rng.next_f64() * 10.,
+            );
+            assert!((0.0..=1.0).contains(&four.turbulence(&point)));
+            assert!((-1.0..=1.0).contains(&four.fbm(&point)));
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{
+    cmp::EPSILON,
+    mathstructs::{matrix::Matrix, point::Point},
+    ray::Ray,
+};
+
+/// axis aligned bounding box. Used to skip whole groups of objects a ray can never hit.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    /// contains nothing. Adding points or boxes to it grows it from there.
+    pub fn empty() -> Self {
+        Self::new(
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        )
+    }
+
+    /// contains everything. Used for shapes like planes that never end.
+    pub fn infinite() -> Self {
+        Self::new(
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+    }
+
+    /// grows the box so the point fits inside
+    pub fn add_point(&mut self, p: Point) {
+        self.min = Point::new(
+            self.min.x.min(p.x),
+            self.min.y.min(p.y),
+            self.min.z.min(p.z),
+        );
+        self.max = Point::new(
+            self.max.x.max(p.x),
+            self.max.y.max(p.y),
+            self.max.z.max(p.z),
+        );
+    }
+
+    /// grows the box so the other box fits inside
+    pub fn merge(&mut self, other: &BoundingBox) {
+        if other.is_empty() {
+            return;
+        }
+        self.add_point(other.min);
+        self.add_point(other.max);
+    }
+
+    pub fn contains_point(&self, p: &Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        self.contains_point(&other.min) && self.contains_point(&other.max)
+    }
+
+    /// box that fits around all 8 transformed corners of this box.
+    /// - boxes that reach into infinity stay infinite (rotating them would only produce NaNs)
+    pub fn transform(&self, m: &Matrix) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        if !self.is_finite() {
+            return Self::infinite();
+        }
+        let (min, max) = (self.min, self.max);
+        let corners = [
+            min,
+            Point::new(min.x, min.y, max.z),
+            Point::new(min.x, max.y, min.z),
+            Point::new(min.x, max.y, max.z),
+            Point::new(max.x, min.y, min.z),
+            Point::new(max.x, min.y, max.z),
+            Point::new(max.x, max.y, min.z),
+            max,
+        ];
+        let mut bounds = Self::empty();
+        for corner in corners {
+            bounds.add_point(*m * corner);
+        }
+        bounds
+    }
+
+    /// same slab-check as the cube, just with our own min and max instead of -1 and 1
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let (x_tmin, x_tmax) =
+            Self::check_axis(ray.origin.x, ray.direction.x, self.min.x, self.max.x);
+        let (y_tmin, y_tmax) =
+            Self::check_axis(ray.origin.y, ray.direction.y, self.min.y, self.max.y);
+        let (z_tmin, z_tmax) =
+            Self::check_axis(ray.origin.z, ray.direction.z, self.min.z, self.max.z);
+        let tmin = f64::max(f64::max(x_tmin, y_tmin), z_tmin);
+        let tmax = f64::min(f64::min(x_tmax, y_tmax), z_tmax);
+        tmin <= tmax
+    }
+
+    fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+        let tmin_numerator = min - origin;
+        let tmax_numerator = max - origin;
+        let (tmin, tmax) = if direction.abs() >= EPSILON {
+            (tmin_numerator / direction, tmax_numerator / direction)
+        } else {
+            (
+                tmin_numerator * f64::INFINITY,
+                tmax_numerator * f64::INFINITY,
+            )
+        };
+        match tmin > tmax {
+            true => (tmax, tmin),
+            false => (tmin, tmax),
+        }
+    }
+
+    /// splits the box in half along its longest axis
+    pub fn split(&self) -> (Self, Self) {
+        let dx = self.max.x - self.min.x;
+        let dy = self.max.y - self.min.y;
+        let dz = self.max.z - self.min.z;
+        let greatest = dx.max(dy).max(dz);
+
+        let (mut x0, mut y0, mut z0) = (self.min.x, self.min.y, self.min.z);
+        let (mut x1, mut y1, mut z1) = (self.max.x, self.max.y, self.max.z);
+        if greatest == dx {
+            x0 += dx / 2.0;
+            x1 = x0;
+        } else if greatest == dy {
+            y0 += dy / 2.0;
+            y1 = y0;
+        } else {
+            z0 += dz / 2.0;
+            z1 = z0;
+        }
+        let mid_min = Point::new(x0, y0, z0);
+        let mid_max = Point::new(x1, y1, z1);
+        (Self::new(self.min, mid_max), Self::new(mid_min, self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{PI, SQRT_2};
+
+    use crate::mathstructs::vector::Vector;
+
+    use super::*;
+
+    #[test]
+    fn creating_an_empty_bounding_box() {
+        let b = BoundingBox::empty();
+        assert!(b.is_empty());
+        assert_eq!(
+            b.min,
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY)
+        );
+        assert_eq!(
+            b.max,
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY)
+        );
+    }
+
+    #[test]
+    fn adding_points_to_an_empty_bounding_box() {
+        let mut b = BoundingBox::empty();
+        b.add_point(Point::new(-5., 2., 0.));
+        b.add_point(Point::new(7., 0., -3.));
+        assert_eq!(b.min, Point::new(-5., 0., -3.));
+        assert_eq!(b.max, Point::new(7., 2., 0.));
+    }
+
+    #[test]
+    fn adding_one_bounding_box_to_another() {
+        let mut b1 = BoundingBox::new(Point::inew(-5, -2, 0), Point::inew(7, 4, 4));
+        let b2 = BoundingBox::new(Point::inew(8, -7, -2), Point::inew(14, 2, 8));
+        b1.merge(&b2);
+        assert_eq!(b1.min, Point::inew(-5, -7, -2));
+        assert_eq!(b1.max, Point::inew(14, 4, 8));
+    }
+
+    #[test]
+    fn checking_if_a_box_contains_a_point_or_box() {
+        let b = BoundingBox::new(Point::inew(5, -2, 0), Point::inew(11, 4, 7));
+        assert!(b.contains_point(&Point::inew(5, -2, 0)));
+        assert!(b.contains_point(&Point::inew(8, 1, 3)));
+        assert!(!b.contains_point(&Point::inew(3, 0, 3)));
+        assert!(!b.contains_point(&Point::inew(8, 1, 8)));
+        assert!(b.contains_box(&BoundingBox::new(
+            Point::inew(6, -1, 1),
+            Point::inew(10, 3, 6)
+        )));
+        assert!(!b.contains_box(&BoundingBox::new(
+            Point::inew(4, -3, -1),
+            Point::inew(10, 3, 6)
+        )));
+    }
+
+    #[test]
+    fn transforming_a_bounding_box() {
+        let b = BoundingBox::new(Point::inew(-1, -1, -1), Point::inew(1, 1, 1));
+        let m = Matrix::rotation_x_new(PI / 4.) * Matrix::rotation_y_new(PI / 4.);
+        let b2 = b.transform(&m);
+        assert_eq!(b2.min, Point::new(-SQRT_2, -1.70711, -1.70711));
+        assert_eq!(b2.max, Point::new(SQRT_2, 1.70711, 1.70711));
+    }
+
+    #[test]
+    fn transforming_an_infinite_bounding_box_stays_infinite() {
+        let b = BoundingBox::infinite().transform(&Matrix::rotation_x_new(PI / 4.));
+        assert_eq!(b, BoundingBox::infinite());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_bounding_box() {
+        let b = BoundingBox::new(Point::inew(5, -2, 0), Point::inew(11, 4, 7));
+        let hits = |origin: Point, direction: Vector| {
+            b.intersects(&Ray::new(origin, direction.normalize()))
+        };
+        assert!(hits(Point::inew(15, 1, 2), Vector::inew(-1, 0, 0)));
+        assert!(hits(Point::inew(8, 2, 12), Vector::inew(0, 0, -1)));
+        assert!(hits(Point::inew(7, 0, 7), Vector::inew(-1, 0, 0)));
+        assert!(hits(Point::inew(8, 1, 3), Vector::inew(0, 0, 1)));
+        assert!(!hits(Point::inew(9, -1, -8), Vector::inew(2, 4, 6)));
+        assert!(!hits(Point::inew(12, 5, 4), Vector::inew(-1, 0, 0)));
+        assert!(!BoundingBox::empty()
+            .intersects(&Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1))));
+    }
+
+    #[test]
+    fn splitting_a_bounding_box() {
+        let b = BoundingBox::new(Point::inew(-1, -4, -5), Point::new(9., 6., 5.));
+        let (left, right) = b.split();
+        assert_eq!(left.min, Point::inew(-1, -4, -5));
+        assert_eq!(left.max, Point::inew(4, 6, 5));
+        assert_eq!(right.min, Point::inew(4, -4, -5));
+        assert_eq!(right.max, Point::inew(9, 6, 5));
+
+        let b = BoundingBox::new(Point::inew(-1, -2, -3), Point::inew(5, 8, 3));
+        let (left, right) = b.split();
+        assert_eq!(left.max, Point::inew(5, 3, 3));
+        assert_eq!(right.min, Point::inew(-1, 3, -3));
+    }
+}
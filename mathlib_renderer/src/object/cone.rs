@@ -0,0 +1,236 @@
+use crate::{
+    cmp::{ApproxEq, EPSILON},
+    mathstructs::{point::Point, vector::Vector},
+    ray::{intersects::IntersectsRay, Ray},
+};
+
+use super::{bounds::BoundingBox, cylinder::Cylinder, Object, Shape};
+
+/// double-napped cone around the y-axis with its tip at (0,0,0). The radius at y is always abs(y).
+/// - infinite in both y-directions unless truncated by minimum and maximum (both exclusive)
+/// - closed adds end caps at minimum and maximum
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cone {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cone {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Object {
+        Object::new(Shape::Cone(Self::default()))
+    }
+
+    /// cone cut off at minimum and maximum on the y-axis. closed adds the end caps.
+    pub fn new_truncated(minimum: f64, maximum: f64, closed: bool) -> Object {
+        Object::new(Shape::Cone(Cone {
+            minimum,
+            maximum,
+            closed,
+        }))
+    }
+
+    /// pushes intersections with the end caps if the cone is closed
+    fn intersect_caps(&self, ray: &Ray, xs: &mut Vec<f64>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+        // unlike the cylinder the radius of the caps is different at each end
+        let t = (self.minimum - ray.origin.y) / ray.direction.y;
+        if Cylinder::check_cap(ray, t, self.minimum.abs()) {
+            xs.push(t);
+        }
+        let t = (self.maximum - ray.origin.y) / ray.direction.y;
+        if Cylinder::check_cap(ray, t, self.maximum.abs()) {
+            xs.push(t);
+        }
+    }
+
+    /// only pushes if the wall-intersection lies between minimum and maximum
+    fn push_if_in_bounds(&self, ray: &Ray, t: f64, xs: &mut Vec<f64>) {
+        let y = ray.origin.y + t * ray.direction.y;
+        if self.minimum < y && y < self.maximum {
+            xs.push(t);
+        }
+    }
+}
+
+impl IntersectsRay for Cone {
+    /// up to 4 intersections possible, if both halves and both caps get hit.
+    fn intersect_raw(&self, ray: &Ray) -> Vec<f64> {
+        let mut xs = Vec::with_capacity(4);
+        let (o, d) = (ray.origin, ray.direction);
+        let a = d.x * d.x - d.y * d.y + d.z * d.z;
+        let b = 2.0 * o.x * d.x - 2.0 * o.y * d.y + 2.0 * o.z * d.z;
+        let c = o.x * o.x - o.y * o.y + o.z * o.z;
+
+        if a.apx_eq(&0.0) {
+            // ray is parallel to one of the cone's halves -> only hits the other half once
+            if !b.apx_eq(&0.0) {
+                self.push_if_in_bounds(ray, -c / (2.0 * b), &mut xs);
+            }
+        } else {
+            let disc = b * b - 4.0 * a * c;
+            if disc >= 0.0 {
+                let mut t0 = (-b - disc.sqrt()) / (2.0 * a);
+                let mut t1 = (-b + disc.sqrt()) / (2.0 * a);
+                if t0 > t1 {
+                    (t0, t1) = (t1, t0);
+                }
+                self.push_if_in_bounds(ray, t0, &mut xs);
+                self.push_if_in_bounds(ray, t1, &mut xs);
+            }
+        }
+        self.intersect_caps(ray, &mut xs);
+        xs
+    }
+
+    fn normal_at(&self, point: Point) -> Vector {
+        // square of the distance from the y-axis
+        let dist = point.x * point.x + point.z * point.z;
+        if dist < self.maximum * self.maximum && point.y >= self.maximum - EPSILON {
+            return Vector::new(0.0, 1.0, 0.0);
+        }
+        if dist < self.minimum * self.minimum && point.y <= self.minimum + EPSILON {
+            return Vector::new(0.0, -1.0, 0.0);
+        }
+        let y = match point.y > 0.0 {
+            true => -dist.sqrt(),
+            false => dist.sqrt(),
+        };
+        Vector::new(point.x, y, point.z)
+    }
+
+    // the radius is the biggest at whichever end is further away from the tip
+    fn bounds(&self) -> BoundingBox {
+        let radius = f64::max(self.minimum.abs(), self.maximum.abs());
+        BoundingBox::new(
+            Point::new(-radius, self.minimum, -radius),
+            Point::new(radius, self.maximum, radius),
+        )
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self {
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mathstructs::{point::Point, vector::Vector},
+        ray::Ray,
+    };
+
+    use super::*;
+
+    fn expect_intersects(origin: Point, direction: Vector, t0: f64, t1: f64) {
+        let c = Cone::default();
+        let ray = Ray::new(origin, direction.normalize());
+        let xs = c.intersect_raw(&ray);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].apx_eq(&t0), "{} != {t0}", xs[0]);
+        assert!(xs[1].apx_eq(&t1), "{} != {t1}", xs[1]);
+    }
+
+    #[test]
+    fn intersecting_a_cone_with_a_ray() {
+        expect_intersects(Point::inew(0, 0, -5), Vector::inew(0, 0, 1), 5., 5.);
+        expect_intersects(
+            Point::inew(0, 0, -5),
+            Vector::inew(1, 1, 1),
+            8.66025,
+            8.66025,
+        );
+        expect_intersects(
+            Point::inew(1, 1, -5),
+            Vector::new(-0.5, -1., 1.),
+            4.55006,
+            49.44994,
+        );
+    }
+
+    #[test]
+    fn intersecting_a_cone_with_a_ray_parallel_to_one_of_its_halves() {
+        let c = Cone::default();
+        let ray = Ray::new(Point::inew(0, 0, -1), Vector::inew(0, 1, 1).normalize());
+        let xs = c.intersect_raw(&ray);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].apx_eq(&0.35355));
+    }
+
+    fn count_intersects(c: &Cone, origin: Point, direction: Vector) -> usize {
+        let ray = Ray::new(origin, direction.normalize());
+        c.intersect_raw(&ray).len()
+    }
+
+    #[test]
+    fn intersecting_a_cones_end_caps() {
+        let c = Cone {
+            minimum: -0.5,
+            maximum: 0.5,
+            closed: true,
+        };
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 0, -5), Vector::inew(0, 1, 0)),
+            0
+        );
+        assert_eq!(
+            count_intersects(&c, Point::new(0., 0., -0.25), Vector::inew(0, 1, 1)),
+            2
+        );
+        assert_eq!(
+            count_intersects(&c, Point::new(0., 0., -0.25), Vector::inew(0, 1, 0)),
+            4
+        );
+    }
+
+    #[test]
+    fn computing_the_normal_vector_on_a_cone() {
+        let c = Cone::default();
+        assert_eq!(c.normal_at(Point::inew(0, 0, 0)), Vector::inew(0, 0, 0));
+        assert_eq!(
+            c.normal_at(Point::inew(1, 1, 1)),
+            Vector::new(1., -(2.0_f64.sqrt()), 1.)
+        );
+        assert_eq!(c.normal_at(Point::inew(-1, -1, 0)), Vector::inew(-1, 1, 0));
+    }
+
+    #[test]
+    fn normal_vector_on_a_cones_end_caps() {
+        let c = Cone::new_truncated(-1., 2., true);
+        assert_eq!(c.normal_at(&Point::new(0.5, 2., 0.)), Vector::inew(0, 1, 0));
+        assert_eq!(
+            c.normal_at(&Point::new(0., -1., 0.5)),
+            Vector::inew(0, -1, 0)
+        );
+    }
+
+    #[test]
+    fn a_cone_has_a_bounding_box() {
+        let b = Cone::default().bounds();
+        assert_eq!(
+            b.min,
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY)
+        );
+        assert_eq!(
+            b.max,
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY)
+        );
+
+        let c = Cone {
+            minimum: -5.,
+            maximum: 3.,
+            closed: false,
+        };
+        assert_eq!(c.bounds().min, Point::inew(-5, -5, -5));
+        assert_eq!(c.bounds().max, Point::inew(5, 3, 5));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::ray::intersects::{Intersect, VecIntersections};
+
+use super::{Object, Shape};
+
+/// how the two operands of a Csg get combined
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CsgOperation {
+    /// everything inside either of the operands
+    Union,
+    /// only what is inside both operands
+    Intersection,
+    /// the left operand with everything inside the right one cut out
+    Difference,
+}
+
+/// constructive solid geometry. Combines two objects (that can be groups or csgs themselves) into one shape.
+/// - like a group the transformation of the csg gets applied on top of both operands
+#[derive(Debug, PartialEq, Clone)]
+pub struct Csg {
+    pub operation: CsgOperation,
+    /// [left, right]. Boxed because the Object itself holds the Shape.
+    pub(super) operands: Box<[Object; 2]>,
+}
+
+impl Csg {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(operation: CsgOperation, left: Object, right: Object) -> Object {
+        let mut csg = Object::new(Shape::Csg(Csg {
+            operation,
+            operands: Box::new([left, right]),
+        }));
+        csg.update_children();
+        csg
+    }
+
+    pub fn left(&self) -> &Object {
+        &self.operands[0]
+    }
+
+    pub fn right(&self) -> &Object {
+        &self.operands[1]
+    }
+
+    /// decides if an intersection is part of the resulting shape
+    /// - left_hit: if the left operand (else the right one) got hit
+    /// - in_left, in_right: if the intersection happens inside the other operands
+    pub fn intersection_allowed(&self, left_hit: bool, in_left: bool, in_right: bool) -> bool {
+        match self.operation {
+            CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+            CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+            CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left),
+        }
+    }
+
+    /// walks the sorted intersections while keeping track if we are inside each operand.
+    /// Only keeps the intersections that are on the surface of the resulting shape.
+    pub fn filter_intersections<'a>(&self, xs: VecIntersections<'a>) -> Vec<Intersect<'a>> {
+        // we start outside of both operands
+        let (mut in_left, mut in_right) = (false, false);
+        let mut result = vec![];
+        for i in xs.0 {
+            let left_hit = self.left().includes(i.object);
+            if self.intersection_allowed(left_hit, in_left, in_right) {
+                result.push(i);
+            }
+            // every intersection toggles being inside the operand that got hit
+            match left_hit {
+                true => in_left = !in_left,
+                false => in_right = !in_right,
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mathstructs::{matrix::Matrix, point::Point, vector::Vector},
+        object::{cube::Cube, group::Group, sphere::Sphere},
+        ray::Ray,
+    };
+
+    use super::*;
+
+    fn csg_shape(obj: &Object) -> &Csg {
+        match obj.shape() {
+            Shape::Csg(csg) => csg,
+            _ => panic!("expected a csg"),
+        }
+    }
+
+    #[test]
+    fn csg_is_created_with_an_operation_and_two_shapes() {
+        let c = Csg::new(CsgOperation::Union, Sphere::new(), Cube::new());
+        let csg = csg_shape(&c);
+        assert_eq!(csg.operation, CsgOperation::Union);
+        assert_eq!(csg.left(), &Sphere::new());
+        assert_eq!(csg.right(), &Cube::new());
+        assert_eq!(c.children().len(), 2);
+    }
+
+    #[test]
+    fn evaluating_the_rule_for_a_csg_operation() {
+        // (operation, left_hit, in_left, in_right, expected)
+        let table = [
+            (CsgOperation::Union, true, true, true, false),
+            (CsgOperation::Union, true, true, false, true),
+            (CsgOperation::Union, true, false, true, false),
+            (CsgOperation::Union, true, false, false, true),
+            (CsgOperation::Union, false, true, true, false),
+            (CsgOperation::Union, false, true, false, false),
+            (CsgOperation::Union, false, false, true, true),
+            (CsgOperation::Union, false, false, false, true),
+            (CsgOperation::Intersection, true, true, true, true),
+            (CsgOperation::Intersection, true, true, false, false),
+            (CsgOperation::Intersection, true, false, true, true),
+            (CsgOperation::Intersection, true, false, false, false),
+            (CsgOperation::Intersection, false, true, true, true),
+            (CsgOperation::Intersection, false, true, false, true),
+            (CsgOperation::Intersection, false, false, true, false),
+            (CsgOperation::Intersection, false, false, false, false),
+            (CsgOperation::Difference, true, true, true, false),
+            (CsgOperation::Difference, true, true, false, true),
+            (CsgOperation::Difference, true, false, true, false),
+            (CsgOperation::Difference, true, false, false, true),
+            (CsgOperation::Difference, false, true, true, true),
+            (CsgOperation::Difference, false, true, false, true),
+            (CsgOperation::Difference, false, false, true, false),
+            (CsgOperation::Difference, false, false, false, false),
+        ];
+        for (operation, left_hit, in_left, in_right, expected) in table {
+            let c = Csg::new(operation, Sphere::new(), Cube::new());
+            let res = csg_shape(&c).intersection_allowed(left_hit, in_left, in_right);
+            assert_eq!(
+                res, expected,
+                "{:?} {left_hit} {in_left} {in_right}",
+                operation
+            );
+        }
+    }
+
+    #[test]
+    fn filtering_a_list_of_intersections() {
+        // (operation, index of first kept intersection, index of second kept intersection)
+        let table = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+        for (operation, x0, x1) in table {
+            let c = Csg::new(operation, Sphere::new(), Cube::new());
+            let csg = csg_shape(&c);
+            let (s1, s2) = (csg.left(), csg.right());
+            let xs = VecIntersections(vec![
+                Intersect::new(1., s1),
+                Intersect::new(2., s2),
+                Intersect::new(3., s1),
+                Intersect::new(4., s2),
+            ]);
+            let expected = [xs.0[x0].clone(), xs.0[x1].clone()];
+            let result = csg.filter_intersections(xs);
+            assert_eq!(result.len(), 2);
+            assert_eq!(result[0].t, expected[0].t);
+            assert_eq!(result[1].t, expected[1].t);
+        }
+    }
+
+    #[test]
+    fn a_ray_misses_a_csg_object() {
+        let c = Csg::new(CsgOperation::Union, Sphere::new(), Cube::new());
+        let ray = Ray::new(Point::inew(0, 2, -5), Vector::inew(0, 0, 1));
+        assert!(c.intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn a_ray_hits_a_csg_object() {
+        let s2 = Sphere::new().with_transform(Matrix::translation_new(0., 0., 0.5));
+        let c = Csg::new(CsgOperation::Union, Sphere::new(), s2);
+        let ray = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1));
+        let xs = c.intersect(&ray);
+        let csg = csg_shape(&c);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.);
+        assert!(std::ptr::eq(xs[0].object, csg.left()));
+        assert_eq!(xs[1].t, 6.5);
+        assert!(std::ptr::eq(xs[1].object, csg.right()));
+    }
+
+    #[test]
+    fn children_of_groups_count_as_part_of_the_operand() {
+        let left = Group::new(vec![Sphere::new()]);
+        let right = Cube::new().with_transform(Matrix::translation_new(0., 0., 1.));
+        let c = Csg::new(CsgOperation::Difference, left, right);
+        let ray = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1));
+        let xs = c.intersect(&ray);
+        // the sphere gets cut off by the cube at z=0
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.);
+        assert_eq!(xs[1].t, 5.);
+    }
+
+    #[test]
+    fn operands_inherit_the_csg_transformation() {
+        let c = Csg::new(CsgOperation::Union, Sphere::new(), Cube::new())
+            .with_transform(Matrix::translation_new(5., 0., 0.));
+        let csg = csg_shape(&c);
+        assert_eq!(
+            csg.left().world_to_obj(Point::inew(5, 0, 0)),
+            Point::inew(0, 0, 0)
+        );
+        let ray = Ray::new(Point::inew(5, 0, -5), Vector::inew(0, 0, 1));
+        assert_eq!(c.intersect(&ray).len(), 2);
+    }
+}
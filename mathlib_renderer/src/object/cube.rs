@@ -1,17 +1,15 @@
-use std::f64::INFINITY;
-
 use crate::{
     cmp::EPSILON,
     mathstructs::{point::Point, vector::Vector},
     ray::{intersects::IntersectsRay, Ray},
 };
 
-use super::{Object, Shape};
+use super::{bounds::BoundingBox, Object, Shape};
 
 pub struct Cube {}
 
 impl IntersectsRay for Cube {
-    fn intersect_raw(&self, ray: &Ray) -> Option<(f64, f64)> {
+    fn intersect_raw(&self, ray: &Ray) -> Vec<f64> {
         let (x_tmin, x_tmax) = Self::check_axis(ray.origin.x, ray.direction.x);
         let (y_tmin, y_tmax) = Self::check_axis(ray.origin.y, ray.direction.y);
         let (z_tmin, z_tmax) = Self::check_axis(ray.origin.z, ray.direction.z);
@@ -19,14 +17,14 @@ impl IntersectsRay for Cube {
         let tmax = f64::min(f64::min(x_tmax, y_tmax), z_tmax);
 
         match tmin > tmax {
-            true => None,
-            false => Some((tmin, tmax)),
+            true => vec![],
+            false => vec![tmin, tmax],
         }
     }
 
     // (1.0,0.3,0.6) -> the value with 1.0 is direction of our normal.
     // BUT because f64 we better check absolute for max here. 1.0 might rounding-error
-    fn normal_at(point: Point) -> Vector {
+    fn normal_at(&self, point: Point) -> Vector {
         let maxc = f64::max(f64::max(point.x.abs(), point.y.abs()), point.z.abs());
         match maxc {
             n if n == point.x.abs() => Vector::new(point.x, 0., 0.),
@@ -34,6 +32,10 @@ impl IntersectsRay for Cube {
             _ => Vector::new(0., 0., point.z),
         }
     }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::inew(-1, -1, -1), Point::inew(1, 1, 1))
+    }
 }
 
 impl Cube {
@@ -51,7 +53,10 @@ impl Cube {
             (tmin_numerator / direction, tmax_numerator / direction)
         } else {
             // cant divide by 0 -> +/- infinity will handle it on f64::max min
-            (tmin_numerator * INFINITY, tmax_numerator * INFINITY)
+            (
+                tmin_numerator * f64::INFINITY,
+                tmax_numerator * f64::INFINITY,
+            )
         };
 
         if tmin > tmax {
@@ -73,8 +78,8 @@ mod tests {
     fn expect_intersects(origin: Point, direction: Vector, t1: f64, t2: f64) {
         let c = Cube::new();
         let ray = Ray::new(origin, direction);
-        let xs = c.intersect_raw(&ray).unwrap();
-        assert_eq!(xs, (t1, t2));
+        let xs = c.intersect_raw(&ray);
+        assert_eq!(xs, vec![t1, t2]);
     }
 
     #[test]
@@ -92,7 +97,7 @@ mod tests {
         let c = Cube::new();
         let ray = Ray::new(origin, direction);
         let xs = c.intersect_raw(&ray);
-        assert_eq!(xs, None);
+        assert!(xs.is_empty());
     }
 
     #[test]
@@ -122,4 +127,11 @@ mod tests {
         expect_normal(Point::new(1., 1., 1.), Vector::inew(1, 0, 0));
         expect_normal(Point::new(-1., -1., -1.), Vector::inew(-1, 0, 0));
     }
+
+    #[test]
+    fn a_cube_has_a_bounding_box() {
+        let b = Cube {}.bounds();
+        assert_eq!(b.min, Point::inew(-1, -1, -1));
+        assert_eq!(b.max, Point::inew(1, 1, 1));
+    }
 }
@@ -0,0 +1,297 @@
+use crate::{
+    cmp::{ApproxEq, EPSILON},
+    mathstructs::{point::Point, vector::Vector},
+    ray::{intersects::IntersectsRay, Ray},
+};
+
+use super::{bounds::BoundingBox, Object, Shape};
+
+/// always of radius 1 and around the y-axis.
+/// - infinite in both y-directions unless truncated by minimum and maximum (both exclusive)
+/// - closed adds end caps at minimum and maximum
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cylinder {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cylinder {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Object {
+        Object::new(Shape::Cylinder(Self::default()))
+    }
+
+    /// cylinder cut off at minimum and maximum on the y-axis. closed adds the end caps.
+    pub fn new_truncated(minimum: f64, maximum: f64, closed: bool) -> Object {
+        Object::new(Shape::Cylinder(Cylinder {
+            minimum,
+            maximum,
+            closed,
+        }))
+    }
+
+    /// checks if the intersection at t is within the radius of a cap (around the y-axis)
+    pub(super) fn check_cap(ray: &Ray, t: f64, radius: f64) -> bool {
+        let x = ray.origin.x + t * ray.direction.x;
+        let z = ray.origin.z + t * ray.direction.z;
+        (x * x + z * z) <= radius * radius
+    }
+
+    /// pushes intersections with the end caps if the cylinder is closed
+    fn intersect_caps(&self, ray: &Ray, xs: &mut Vec<f64>) {
+        // caps only matter if closed and might possibly be intersected by the ray
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+        // check for intersection with the lower end cap
+        let t = (self.minimum - ray.origin.y) / ray.direction.y;
+        if Self::check_cap(ray, t, 1.0) {
+            xs.push(t);
+        }
+        // check for intersection with the upper end cap
+        let t = (self.maximum - ray.origin.y) / ray.direction.y;
+        if Self::check_cap(ray, t, 1.0) {
+            xs.push(t);
+        }
+    }
+}
+
+impl IntersectsRay for Cylinder {
+    fn intersect_raw(&self, ray: &Ray) -> Vec<f64> {
+        let mut xs = Vec::with_capacity(4);
+        let a = ray.direction.x * ray.direction.x + ray.direction.z * ray.direction.z;
+
+        // ray is parallel to the y axis -> it can only hit the caps
+        if !a.apx_eq(&0.0) {
+            let b = 2.0 * ray.origin.x * ray.direction.x + 2.0 * ray.origin.z * ray.direction.z;
+            let c = ray.origin.x * ray.origin.x + ray.origin.z * ray.origin.z - 1.0;
+            let disc = b * b - 4.0 * a * c;
+            // ray does not intersect the cylinder
+            if disc < 0.0 {
+                return xs;
+            }
+
+            let mut t0 = (-b - disc.sqrt()) / (2.0 * a);
+            let mut t1 = (-b + disc.sqrt()) / (2.0 * a);
+            if t0 > t1 {
+                (t0, t1) = (t1, t0);
+            }
+
+            // only keep the walls between minimum and maximum
+            let y0 = ray.origin.y + t0 * ray.direction.y;
+            if self.minimum < y0 && y0 < self.maximum {
+                xs.push(t0);
+            }
+            let y1 = ray.origin.y + t1 * ray.direction.y;
+            if self.minimum < y1 && y1 < self.maximum {
+                xs.push(t1);
+            }
+        }
+        self.intersect_caps(ray, &mut xs);
+        xs
+    }
+
+    fn normal_at(&self, point: Point) -> Vector {
+        // square of the distance from the y-axis
+        let dist = point.x * point.x + point.z * point.z;
+        if dist < 1.0 && point.y >= self.maximum - EPSILON {
+            Vector::new(0.0, 1.0, 0.0)
+        } else if dist < 1.0 && point.y <= self.minimum + EPSILON {
+            Vector::new(0.0, -1.0, 0.0)
+        } else {
+            Vector::new(point.x, 0.0, point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(-1.0, self.minimum, -1.0),
+            Point::new(1.0, self.maximum, 1.0),
+        )
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self {
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mathstructs::{matrix::Matrix, point::Point, vector::Vector},
+        ray::Ray,
+    };
+
+    use super::*;
+
+    fn expect_no_intersects(origin: Point, direction: Vector) {
+        let c = Cylinder::default();
+        let ray = Ray::new(origin, direction.normalize());
+        assert!(c.intersect_raw(&ray).is_empty());
+    }
+
+    #[test]
+    fn ray_misses_a_cylinder() {
+        expect_no_intersects(Point::inew(1, 0, 0), Vector::inew(0, 1, 0));
+        expect_no_intersects(Point::inew(0, 0, 0), Vector::inew(0, 1, 0));
+        expect_no_intersects(Point::inew(0, 0, -5), Vector::inew(1, 1, 1));
+    }
+
+    fn expect_intersects(origin: Point, direction: Vector, t0: f64, t1: f64) {
+        let c = Cylinder::default();
+        let ray = Ray::new(origin, direction.normalize());
+        let xs = c.intersect_raw(&ray);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].apx_eq(&t0), "{} != {t0}", xs[0]);
+        assert!(xs[1].apx_eq(&t1), "{} != {t1}", xs[1]);
+    }
+
+    #[test]
+    fn ray_strikes_a_cylinder() {
+        expect_intersects(Point::inew(1, 0, -5), Vector::inew(0, 0, 1), 5., 5.);
+        expect_intersects(Point::inew(0, 0, -5), Vector::inew(0, 0, 1), 4., 6.);
+        expect_intersects(
+            Point::new(0.5, 0., -5.),
+            Vector::new(0.1, 1., 1.),
+            6.80798,
+            7.08872,
+        );
+    }
+
+    fn expect_normal(point: Point, normal: Vector) {
+        let c = Cylinder::new();
+        assert_eq!(c.normal_at(&point), normal);
+    }
+
+    #[test]
+    fn normal_vector_on_a_cylinder() {
+        expect_normal(Point::inew(1, 0, 0), Vector::inew(1, 0, 0));
+        expect_normal(Point::inew(0, 5, -1), Vector::inew(0, 0, -1));
+        expect_normal(Point::inew(0, -2, 1), Vector::inew(0, 0, 1));
+        expect_normal(Point::inew(-1, 1, 0), Vector::inew(-1, 0, 0));
+    }
+
+    #[test]
+    fn default_minimum_and_maximum_for_a_cylinder() {
+        let c = Cylinder::default();
+        assert_eq!(c.minimum, f64::NEG_INFINITY);
+        assert_eq!(c.maximum, f64::INFINITY);
+        assert!(!c.closed);
+    }
+
+    fn count_intersects(c: &Cylinder, origin: Point, direction: Vector) -> usize {
+        let ray = Ray::new(origin, direction.normalize());
+        c.intersect_raw(&ray).len()
+    }
+
+    #[test]
+    fn intersecting_a_constrained_cylinder() {
+        let c = Cylinder {
+            minimum: 1.,
+            maximum: 2.,
+            closed: false,
+        };
+        assert_eq!(
+            count_intersects(&c, Point::new(0., 1.5, 0.), Vector::new(0.1, 1., 0.)),
+            0
+        );
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 3, -5), Vector::inew(0, 0, 1)),
+            0
+        );
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 0, -5), Vector::inew(0, 0, 1)),
+            0
+        );
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 2, -5), Vector::inew(0, 0, 1)),
+            0
+        );
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 1, -5), Vector::inew(0, 0, 1)),
+            0
+        );
+        assert_eq!(
+            count_intersects(&c, Point::new(0., 1.5, -2.), Vector::inew(0, 0, 1)),
+            2
+        );
+    }
+
+    #[test]
+    fn intersecting_the_caps_of_a_closed_cylinder() {
+        let c = Cylinder {
+            minimum: 1.,
+            maximum: 2.,
+            closed: true,
+        };
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 3, 0), Vector::inew(0, -1, 0)),
+            2
+        );
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 3, -2), Vector::inew(0, -1, 2)),
+            2
+        );
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 4, -2), Vector::inew(0, -1, 1)),
+            2
+        );
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, 0, -2), Vector::inew(0, 1, 2)),
+            2
+        );
+        assert_eq!(
+            count_intersects(&c, Point::inew(0, -1, -2), Vector::inew(0, 1, 1)),
+            2
+        );
+    }
+
+    #[test]
+    fn normal_vector_on_a_cylinders_end_caps() {
+        let c = Cylinder::new_truncated(1., 2., true);
+        assert_eq!(c.normal_at(&Point::inew(0, 1, 0)), Vector::inew(0, -1, 0));
+        assert_eq!(
+            c.normal_at(&Point::new(0.5, 1., 0.)),
+            Vector::inew(0, -1, 0)
+        );
+        assert_eq!(
+            c.normal_at(&Point::new(0., 1., 0.5)),
+            Vector::inew(0, -1, 0)
+        );
+        assert_eq!(c.normal_at(&Point::inew(0, 2, 0)), Vector::inew(0, 1, 0));
+        assert_eq!(c.normal_at(&Point::new(0.5, 2., 0.)), Vector::inew(0, 1, 0));
+        assert_eq!(c.normal_at(&Point::new(0., 2., 0.5)), Vector::inew(0, 1, 0));
+    }
+
+    #[test]
+    fn truncated_cylinder_keeps_its_transformation() {
+        let c = Cylinder::new_truncated(0., 1., true)
+            .with_transform(Matrix::translation_new(0., 1., 0.));
+        let ray = Ray::new(Point::inew(0, 5, 0), Vector::inew(0, -1, 0));
+        let mut xs = c.intersect_raw(&ray);
+        xs.sort_by(f64::total_cmp);
+        assert_eq!(xs, vec![3., 4.]);
+    }
+
+    #[test]
+    fn a_cylinder_has_a_bounding_box() {
+        let b = Cylinder::default().bounds();
+        assert_eq!(b.min, Point::new(-1., f64::NEG_INFINITY, -1.));
+        assert_eq!(b.max, Point::new(1., f64::INFINITY, 1.));
+
+        let c = Cylinder {
+            minimum: -5.,
+            maximum: 3.,
+            closed: false,
+        };
+        assert_eq!(c.bounds().min, Point::inew(-1, -5, -1));
+        assert_eq!(c.bounds().max, Point::inew(1, 3, 1));
+    }
+}
@@ -0,0 +1,203 @@
+use super::{Object, Shape};
+
+/// a collection of objects that get transformed together.
+/// - the group's transformation gets applied on top of each child's own transformation
+pub struct Group {}
+
+impl Group {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(children: Vec<Object>) -> Object {
+        // pushing all children at once, so we only update them once (instead of every add_child)
+        let mut group = Object::new(Shape::Group(children));
+        group.update_children();
+        group
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        mathstructs::{matrix::Matrix, point::Point, vector::Vector},
+        object::{cylinder::Cylinder, plane::Plane, sphere::Sphere},
+        ray::{intersects::VecIntersections, Ray},
+        visual::{color::Col, patterns::Pattern},
+    };
+
+    use super::*;
+
+    #[test]
+    fn creating_a_new_group() {
+        let g = Group::new(vec![]);
+        assert_eq!(*g.transformation(), Matrix::new_identity());
+        assert!(g.children().is_empty());
+    }
+
+    #[test]
+    fn adding_a_child_to_a_group() {
+        let mut g = Group::new(vec![]);
+        g.add_child(Sphere::new());
+        assert_eq!(g.children().len(), 1);
+        assert_eq!(g.children()[0], Sphere::new());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_an_empty_group() {
+        let g = Group::new(vec![]);
+        let ray = Ray::new(Point::inew(0, 0, 0), Vector::inew(0, 0, 1));
+        let mut xs = VecIntersections::new();
+        xs.intersect_add(&ray, &g);
+        assert!(xs.is_empty());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_nonempty_group() {
+        let s1 = Sphere::new();
+        let s2 = Sphere::new().with_transform(Matrix::translation_new(0., 0., -3.));
+        let s3 = Sphere::new().with_transform(Matrix::translation_new(5., 0., 0.));
+        let g = Group::new(vec![s1, s2, s3]);
+        let ray = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1));
+        let mut xs = VecIntersections::new();
+        xs.intersect_add(&ray, &g);
+
+        let children = g.children();
+        let hits: Vec<_> = xs.iter().map(|i| i.object as *const _).collect();
+        assert_eq!(xs.len(), 4);
+        assert_eq!(hits[0], &children[1] as *const _);
+        assert_eq!(hits[1], &children[1] as *const _);
+        assert_eq!(hits[2], &children[0] as *const _);
+        assert_eq!(hits[3], &children[0] as *const _);
+    }
+
+    #[test]
+    fn intersecting_a_transformed_group() {
+        let s = Sphere::new().with_transform(Matrix::translation_new(5., 0., 0.));
+        let g = Group::new(vec![s]).with_transform(Matrix::scaling_new(2., 2., 2.));
+        let ray = Ray::new(Point::inew(10, 0, -10), Vector::inew(0, 0, 1));
+        let mut xs = VecIntersections::new();
+        xs.intersect_add(&ray, &g);
+        assert_eq!(xs.len(), 2);
+    }
+
+    fn nested_groups(child: Object, inner_transform: Matrix) -> Object {
+        let g2 = Group::new(vec![child]).with_transform(inner_transform);
+        Group::new(vec![g2]).with_transform(Matrix::rotation_y_new(PI / 2.))
+    }
+
+    #[test]
+    fn converting_a_point_from_world_to_object_space() {
+        let s = Sphere::new().with_transform(Matrix::translation_new(5., 0., 0.));
+        let g1 = nested_groups(s, Matrix::scaling_new(2., 2., 2.));
+        let s = &g1.children()[0].children()[0];
+        let p = s.world_to_obj(Point::inew(-2, 0, -10));
+        assert_eq!(p, Point::new(0., 0., -1.));
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_child_object() {
+        let s = Sphere::new().with_transform(Matrix::translation_new(5., 0., 0.));
+        let g1 = nested_groups(s, Matrix::scaling_new(1., 2., 3.));
+        let s = &g1.children()[0].children()[0];
+        let n = s.normal_at(&Point::new(1.7321, 1.1547, -5.5774));
+        assert_eq!(n, Vector::new(0.2857, 0.42854, -0.85716));
+    }
+
+    #[test]
+    fn transforming_the_group_after_adding_children_updates_them() {
+        let mut g1 = nested_groups(Sphere::new(), Matrix::scaling_new(1., 2., 3.));
+        g1.set_transform(Matrix::translation_new(0., 10., 0.));
+        let s = &g1.children()[0].children()[0];
+        assert_eq!(
+            s.world_to_obj(Point::inew(0, 11, 0)),
+            Point::new(0., 0.5, 0.)
+        );
+    }
+
+    #[test]
+    fn pattern_on_a_child_uses_the_group_transformation() {
+        let s = Sphere::new();
+        let g = Group::new(vec![s]).with_transform(Matrix::scaling_new(2., 2., 2.));
+        let pattern = Pattern::new_test_pattern();
+        let res = pattern.at_with_obj(&g.children()[0], &Point::inew(2, 3, 4));
+        assert_eq!(res, Col::new(1., 1.5, 2.));
+    }
+
+    #[test]
+    fn a_group_has_a_bounding_box_that_contains_its_children() {
+        let s = Sphere::new()
+            .with_transform(Matrix::translation_new(2., 5., -3.) * Matrix::scaling_new(2., 2., 2.));
+        let c = Cylinder::new_truncated(-2., 2., false).with_transform(
+            Matrix::translation_new(-4., -1., 4.) * Matrix::scaling_new(0.5, 1., 0.5),
+        );
+        let g = Group::new(vec![s, c]);
+        assert_eq!(g.bounds().min, Point::new(-4.5, -3., -5.));
+        assert_eq!(g.bounds().max, Point::new(4., 7., 4.5));
+    }
+
+    #[test]
+    fn bounds_of_a_group_follow_its_transformation() {
+        let s = Sphere::new().with_transform(Matrix::translation_new(5., 0., 0.));
+        let g = nested_groups(s, Matrix::scaling_new(2., 2., 2.));
+        let b = g.bounds();
+        assert_eq!(b.min, Point::new(-2., -2., -12.));
+        assert_eq!(b.max, Point::new(2., 2., -8.));
+    }
+
+    #[test]
+    fn a_ray_missing_the_bounds_does_not_hit_the_children() {
+        let g = Group::new(vec![Sphere::new()]);
+        let ray = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 1, 0));
+        assert!(g.intersect(&ray).is_empty());
+        assert!(g.intersect_raw(&ray).is_empty());
+    }
+
+    #[test]
+    fn dividing_a_group_partitions_its_children() {
+        let s1 = Sphere::new().with_transform(Matrix::translation_new(-2., -2., 0.));
+        let s2 = Sphere::new().with_transform(Matrix::translation_new(-2., 2., 0.));
+        let s3 = Sphere::new().with_transform(Matrix::scaling_new(4., 4., 4.));
+        let mut g = Group::new(vec![s1.clone(), s2.clone(), s3.clone()]);
+        g.divide(1);
+        let children = g.children();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0], s3);
+        let subgroup = children[1].children();
+        assert_eq!(subgroup.len(), 2);
+        assert_eq!(subgroup[0].children(), &[s1]);
+        assert_eq!(subgroup[1].children(), &[s2]);
+    }
+
+    #[test]
+    fn dividing_a_group_with_too_few_children() {
+        let s1 = Sphere::new().with_transform(Matrix::translation_new(-2., 0., 0.));
+        let s2 = Sphere::new().with_transform(Matrix::translation_new(2., 1., 0.));
+        let s3 = Sphere::new().with_transform(Matrix::translation_new(2., -1., 0.));
+        let s4 = Sphere::new();
+        let subgroup = Group::new(vec![s1.clone(), s2.clone(), s3.clone()]);
+        let mut g = Group::new(vec![subgroup, s4.clone()]);
+        g.divide(3);
+        let children = g.children();
+        assert_eq!(children[0].children().len(), 2);
+        assert_eq!(children[0].children()[0].children(), &[s1]);
+        assert_eq!(children[0].children()[1].children(), &[s2, s3]);
+        assert_eq!(children[1], s4);
+    }
+
+    #[test]
+    fn dividing_keeps_infinite_children_and_the_transformation() {
+        let plane = Plane::new();
+        let s1 = Sphere::new().with_transform(Matrix::translation_new(-2., 0., 0.));
+        let s2 = Sphere::new().with_transform(Matrix::translation_new(2., 0., 0.));
+        let mut g = Group::new(vec![plane, s1, s2]).with_transform(Matrix::scaling_new(2., 2., 2.));
+        g.divide(1);
+        assert_eq!(g.children().len(), 3);
+        assert_eq!(g.children()[0].shape(), &Shape::Plane);
+
+        let ray = Ray::new(Point::inew(4, 5, 0), Vector::inew(0, -1, 0));
+        let mut xs = VecIntersections::new();
+        xs.intersect_add(&ray, &g);
+        assert_eq!(xs.len(), 3);
+        assert_eq!(xs.hit().unwrap().t, 3.);
+    }
+}
@@ -1,53 +1,155 @@
+pub mod bounds;
+pub mod cone;
+pub mod csg;
 pub mod cube;
+pub mod cylinder;
+pub mod group;
 pub mod plane;
+pub mod smooth_triangle;
 pub mod sphere;
+pub mod triangle;
 
 use crate::{
     mathstructs::{matrix::Matrix, point::Point, vector::Vector},
-    ray::{intersects::IntersectsRay, Ray},
+    ray::{
+        intersects::{Intersect, IntersectsRay, VecIntersections},
+        Ray,
+    },
     visual::material::Material,
 };
 
-use self::{cube::Cube, plane::Plane, sphere::Sphere};
+use self::{
+    bounds::BoundingBox, cone::Cone, csg::Csg, cube::Cube, cylinder::Cylinder, plane::Plane,
+    smooth_triangle::SmoothTriangle, sphere::Sphere, triangle::Triangle,
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Shape {
     Sphere, // Sphere has no state so i guess no need to actually wrap the shape-struct here
     Plane,
     Cube,
+    Cylinder(Cylinder),
+    Cone(Cone),
+    Triangle(Triangle),
+    SmoothTriangle(SmoothTriangle),
+    /// holds child objects that inherit the transformation of the group
+    Group(Vec<Object>),
+    /// combines two objects that inherit the transformation of the csg
+    Csg(Csg),
 }
 
 /// wrapper that represents a shape like a Sphere and applied transformations etc.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Object {
     shape: Shape,
-    pub transformation: Matrix,
+    /// transformation relative to the parent group (or to the world if not in a group)
+    /// - set it with set_transform() or with_transform() so the cached inverses (and children) get updated
+    transformation: Matrix,
     pub material: Material,
+    /// combined transformations of all parent groups. Identity if not inside a group.
+    parent_transformation: Matrix,
+    /// cached inverse of the world_transformation. Inverting on every ray is way too expensive.
+    inverse: Matrix,
+    /// cached transposed inverse of the world_transformation. Used to get normals back to world space.
+    inverse_transpose: Matrix,
+    /// cached world-space bounds of all children. Only used by groups and csgs.
+    children_bounds: BoundingBox,
 }
 
 impl Object {
-    pub fn intersect_raw(&self, ray: &Ray) -> Option<(f64, f64)> {
+    /// all intersections of the ray with this object. For groups these reference the hit children.
+    pub fn intersect<'a>(&'a self, ray: &Ray) -> Vec<Intersect<'a>> {
+        match &self.shape {
+            // if the ray misses the bounds it can't hit any of the children
+            Shape::Group(_) | Shape::Csg(_) if !self.children_bounds.intersects(ray) => vec![],
+            Shape::Group(children) => children
+                .iter()
+                .flat_map(|child| child.intersect(ray))
+                .collect(),
+            // the csg needs all intersections of both sides sorted to decide which ones to keep
+            Shape::Csg(csg) => {
+                let mut xs = VecIntersections::new();
+                xs.intersect_add(ray, csg.left());
+                xs.intersect_add(ray, csg.right());
+                csg.filter_intersections(xs)
+            }
+            // triangles also remember where on their surface they got hit
+            Shape::Triangle(triangle) => self.intersect_triangle(triangle, ray),
+            Shape::SmoothTriangle(smooth) => self.intersect_triangle(&smooth.triangle, ray),
+            _ => self
+                .intersect_raw(ray)
+                .into_iter()
+                .map(|t| Intersect::new(t, self))
+                .collect(),
+        }
+    }
+
+    fn intersect_triangle<'a>(&'a self, triangle: &Triangle, ray: &Ray) -> Vec<Intersect<'a>> {
+        let ray = ray.transform(&self.inverse);
+        triangle
+            .intersect_uv(&ray)
+            .map(|(t, u, v)| Intersect::new_with_uv(t, self, u, v))
+            .into_iter()
+            .collect()
+    }
+
+    pub fn intersect_raw(&self, ray: &Ray) -> Vec<f64> {
+        // children already know the transformations of their parents, so they take the world-ray
+        if let Shape::Group(children) = &self.shape {
+            if !self.children_bounds.intersects(ray) {
+                return vec![];
+            }
+            return children.iter().flat_map(|c| c.intersect_raw(ray)).collect();
+        }
+        if let Shape::Csg(_) = &self.shape {
+            return self.intersect(ray).iter().map(|i| i.t).collect();
+        }
+
         // to translate from worldspace to objectspace - aka swap choordinate-system
         // we transform the ray itself by the inverse of the .transformation Matrix
-        let ray = ray.transform(&self.transformation.inverse());
+        let ray = ray.transform(&self.inverse);
 
         match &self.shape {
             Shape::Sphere => Sphere {}.intersect_raw(&ray),
             Shape::Plane => Plane {}.intersect_raw(&ray),
             Shape::Cube => Cube {}.intersect_raw(&ray),
+            Shape::Cylinder(cylinder) => cylinder.intersect_raw(&ray),
+            Shape::Cone(cone) => cone.intersect_raw(&ray),
+            Shape::Triangle(triangle) => triangle.intersect_raw(&ray),
+            Shape::SmoothTriangle(smooth) => smooth.intersect_raw(&ray),
+            Shape::Group(_) | Shape::Csg(_) => unreachable!(),
         }
     }
 
-    #[allow(clippy::let_and_return)]
     /// gets point perpendicular to surface.
     pub fn normal_at(&self, world_point: &Point) -> Vector {
+        self.normal_at_uv(world_point, None)
+    }
+
+    /// gets point perpendicular to surface. Smooth triangles use the (u, v) of the hit for this.
+    pub fn normal_at_hit(&self, world_point: &Point, hit: &Intersect) -> Vector {
+        self.normal_at_uv(world_point, Some((hit.u, hit.v)))
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn normal_at_uv(&self, world_point: &Point, uv: Option<(f64, f64)>) -> Vector {
         // transform to object's choordinate system
         let object_point = self.world_to_obj(*world_point);
         // do the shape's normal_at implementation
         let object_normal = match &self.shape {
-            Shape::Sphere => Sphere::normal_at(object_point),
-            Shape::Plane => Plane::normal_at(object_point),
-            Shape::Cube => Cube::normal_at(object_point),
+            Shape::Sphere => Sphere {}.normal_at(object_point),
+            Shape::Plane => Plane {}.normal_at(object_point),
+            Shape::Cube => Cube {}.normal_at(object_point),
+            Shape::Cylinder(cylinder) => cylinder.normal_at(object_point),
+            Shape::Cone(cone) => cone.normal_at(object_point),
+            Shape::Triangle(triangle) => triangle.normal_at(object_point),
+            Shape::SmoothTriangle(smooth) => match uv {
+                Some((u, v)) => smooth.normal_at_uv(u, v),
+                None => smooth.normal_at(object_point),
+            },
+            Shape::Group(_) | Shape::Csg(_) => {
+                unreachable!("groups never get hit directly, only their children")
+            }
         };
         // transform back to world choordinates:
         let world_normal = self.obj_to_world(object_normal).normalize();
@@ -63,23 +165,171 @@ impl Object {
         }
     }
 
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    /// the child objects if this is a group (or the left and right operand of a csg). Empty otherwise.
+    pub fn children(&self) -> &[Object] {
+        match &self.shape {
+            Shape::Group(children) => children,
+            Shape::Csg(csg) => &csg.operands[..],
+            _ => &[],
+        }
+    }
+
+    fn children_mut(&mut self) -> &mut [Object] {
+        match &mut self.shape {
+            Shape::Group(children) => children,
+            Shape::Csg(csg) => &mut csg.operands[..],
+            _ => &mut [],
+        }
+    }
+
+    /// if other is this object or (recursively) one of its children
+    pub fn includes(&self, other: &Object) -> bool {
+        std::ptr::eq(self, other) || self.children().iter().any(|child| child.includes(other))
+    }
+
+    /// axis aligned box in world space that fits around the object (and all its children)
+    pub fn bounds(&self) -> BoundingBox {
+        let local_bounds = match &self.shape {
+            Shape::Group(_) | Shape::Csg(_) => return self.children_bounds,
+            Shape::Sphere => Sphere {}.bounds(),
+            Shape::Plane => Plane {}.bounds(),
+            Shape::Cube => Cube {}.bounds(),
+            Shape::Cylinder(cylinder) => cylinder.bounds(),
+            Shape::Cone(cone) => cone.bounds(),
+            Shape::Triangle(triangle) => triangle.bounds(),
+            Shape::SmoothTriangle(smooth) => smooth.bounds(),
+        };
+        local_bounds.transform(&self.world_transformation())
+    }
+
+    /// transformation relative to the parent group (or to the world if not in a group)
+    pub fn transformation(&self) -> &Matrix {
+        &self.transformation
+    }
+
+    /// combined transformation of this object and all the groups it is part of
+    fn world_transformation(&self) -> Matrix {
+        self.parent_transformation * self.transformation
+    }
+
     /// translates between choordinate systems. World_point to object_point
-    fn world_to_obj(&self, world_point: Point) -> Point {
-        self.transformation.inverse() * world_point
+    pub(crate) fn world_to_obj(&self, world_point: Point) -> Point {
+        self.inverse * world_point
     }
 
     /// translates between choordinate systems. Object_vector to world_vector
     fn obj_to_world(&self, object_normal: Vector) -> Vector {
-        (self.transformation.inverse()).transpose() * object_normal
+        self.inverse_transpose * object_normal
+    }
+
+    /// recalculates the cached inverses. Has to happen after every change of a transformation.
+    fn update_inverse(&mut self) {
+        self.inverse = self.world_transformation().inverse();
+        self.inverse_transpose = self.inverse.transpose();
+    }
+
+    fn set_parent_transformation(&mut self, m: Matrix) {
+        self.parent_transformation = m;
+        self.update_inverse();
+        self.update_children();
+    }
+
+    /// passes our world_transformation down to all children (and their children)
+    /// - also recalculates the bounds of groups, since those depend on the children's transformations
+    fn update_children(&mut self) {
+        if !matches!(self.shape, Shape::Group(_) | Shape::Csg(_)) {
+            return;
+        }
+        let world_transformation = self.world_transformation();
+        let mut bounds = BoundingBox::empty();
+        for child in self.children_mut() {
+            child.set_parent_transformation(world_transformation);
+            bounds.merge(&child.bounds());
+        }
+        self.children_bounds = bounds;
     }
 }
 
 impl Object {
     pub fn with_transform(mut self, m: Matrix) -> Self {
-        self.transformation = m;
+        self.set_transform(m);
         self
     }
 
+    /// sets the transformation and updates the children if this is a group
+    pub fn set_transform(&mut self, m: Matrix) {
+        self.transformation = m;
+        self.update_inverse();
+        self.update_children();
+    }
+
+    /// adds the child to this group. The child inherits the transformation of the group.
+    /// - panics if this object is no group
+    pub fn add_child(&mut self, child: Object) {
+        match &mut self.shape {
+            Shape::Group(children) => children.push(child),
+            _ => panic!("can only add children to a group"),
+        }
+        self.update_children();
+    }
+
+    /// builds a bounding volume hierarchy out of groups: children get sorted into subgroups
+    /// by the half of the group's bounds they fit into. Rays then skip all subgroups they miss.
+    /// - groups with less than threshold children don't get split any further
+    /// - children that fit in neither half (or are infinite, like planes) stay where they are
+    pub fn divide(&mut self, threshold: usize) {
+        let world_transformation = self.world_transformation();
+        // the operands of a csg have to stay as they are, but they might hold groups themselves
+        if let Shape::Csg(csg) = &mut self.shape {
+            csg.operands.iter_mut().for_each(|op| op.divide(threshold));
+            return;
+        }
+        let Shape::Group(children) = &mut self.shape else {
+            return;
+        };
+        if children.len() >= threshold {
+            // infinite bounds can't be split in half, so we only look at the finite ones
+            let mut finite_bounds = BoundingBox::empty();
+            children
+                .iter()
+                .map(|child| child.bounds())
+                .filter(|bounds| bounds.is_finite())
+                .for_each(|bounds| finite_bounds.merge(&bounds));
+            let (left_bounds, right_bounds) = finite_bounds.split();
+
+            let count = children.len();
+            let (left, rest): (Vec<_>, Vec<_>) = std::mem::take(children)
+                .into_iter()
+                .partition(|child| left_bounds.contains_box(&child.bounds()));
+            let (right, mut rest): (Vec<_>, Vec<_>) = rest
+                .into_iter()
+                .partition(|child| right_bounds.contains_box(&child.bounds()));
+            for subgroup in [left, right] {
+                match subgroup.len() {
+                    0 => (),
+                    // a subgroup holding everything would only add another layer to the tree
+                    n if n == count => rest = subgroup,
+                    _ => rest.push(Self::subgroup(subgroup, world_transformation)),
+                }
+            }
+            *children = rest;
+        }
+        for child in children {
+            child.divide(threshold);
+        }
+    }
+
+    /// new group (without transformation of its own) that sits inside a parent with that world_transformation
+    fn subgroup(children: Vec<Object>, parent_transformation: Matrix) -> Object {
+        let mut group = Object::new(Shape::Group(children));
+        group.set_parent_transformation(parent_transformation);
+        group
+    }
+
     pub fn with_refrative_index(mut self, f: f64) -> Self {
         self.material.refractive_index = f;
         self
@@ -92,9 +342,37 @@ impl Default for Object {
             shape: Shape::Sphere,
             transformation: Matrix::new_identity(),
             material: Material::default(),
+            parent_transformation: Matrix::new_identity(),
+            inverse: Matrix::new_identity(),
+            inverse_transpose: Matrix::new_identity(),
+            children_bounds: BoundingBox::empty(),
         }
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use crate::object::{group::Group, sphere::Sphere};
+
+    use super::*;
+
+    #[test]
+    fn setting_the_transform_updates_the_cached_inverses() {
+        let mut s = Sphere::new();
+        let t = Matrix::translation_new(1., 2., 3.) * Matrix::scaling_new(2., 2., 2.);
+        s.set_transform(t);
+        assert_eq!(s.transformation(), &t);
+        assert_eq!(s.inverse, t.inverse());
+        assert_eq!(s.inverse_transpose, t.inverse().transpose());
+    }
+
+    #[test]
+    fn children_cache_the_inverse_of_their_world_transformation() {
+        let child = Sphere::new().with_transform(Matrix::translation_new(5., 0., 0.));
+        let mut g = Group::new(vec![child]);
+        g.set_transform(Matrix::scaling_new(2., 2., 2.));
+        let expected =
+            (Matrix::scaling_new(2., 2., 2.) * Matrix::translation_new(5., 0., 0.)).inverse();
+        assert_eq!(g.children()[0].inverse, expected);
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     ray::intersects::IntersectsRay,
 };
 
-use super::{Object, Shape};
+use super::{bounds::BoundingBox, Object, Shape};
 
 /// Like Sphere plane doesnt hold any state itself and is only there for consistency/structure
 /// - the unit plane is infinite in both x and z.
@@ -19,19 +19,27 @@ impl Plane {
 
 impl IntersectsRay for Plane {
     //
-    fn intersect_raw(&self, ray: &crate::ray::Ray) -> Option<(f64, f64)> {
+    fn intersect_raw(&self, ray: &crate::ray::Ray) -> Vec<f64> {
         // ray is parallel to the plance
         if ray.direction.y.abs() < EPSILON {
-            return None;
+            return vec![];
         }
         let t = -ray.origin.y / ray.direction.y;
-        Some((t, t)) // Note: book returns only one here. length 1. might have to rewrite later
+        vec![t]
     }
 
     // since a plane has no curvature it's always the same:
-    fn normal_at(_point: Point) -> Vector {
+    fn normal_at(&self, _point: Point) -> Vector {
         Vector::new(0.0, 1.0, 0.0)
     }
+
+    // infinite in x and z but flat on y
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -58,28 +66,28 @@ mod tests {
     fn intersect_with_ray_parallel_to_the_plane() {
         let ray = Ray::new(Point::inew(0, 10, 0), Vector::inew(0, 0, 1));
         let xs = &Plane {}.intersect_raw(&ray);
-        assert_eq!(xs, &None);
+        assert!(xs.is_empty());
     }
 
     #[test]
     fn intersect_with_a_coplanar_ray() {
         let ray = Ray::new(Point::inew(0, 0, 0), Vector::inew(0, 0, 1));
         let xs = &Plane {}.intersect_raw(&ray);
-        assert_eq!(xs, &None);
+        assert!(xs.is_empty());
     }
 
     #[test]
     fn a_ray_intersecting_a_plane_from_above() {
         let ray = Ray::new(Point::inew(0, 1, 0), Vector::inew(0, -1, 0));
         let xs = &Plane {}.intersect_raw(&ray);
-        assert_eq!(*xs, Some((1.0, 1.0)));
+        assert_eq!(*xs, vec![1.0]);
     }
 
     #[test]
     fn a_ray_intersecting_a_plane_from_below() {
         let ray = Ray::new(Point::inew(0, -1, 0), Vector::inew(0, 1, 0));
         let xs = &Plane {}.intersect_raw(&ray);
-        assert_eq!(*xs, Some((1.0, 1.0)));
+        assert_eq!(*xs, vec![1.0]);
 
         // comparing the reference reference
         let obj = Plane::new();
@@ -88,4 +96,11 @@ mod tests {
         let hit = inters.hit().unwrap().object;
         assert_eq!(&obj as *const _, hit as *const _); // again comparing pointer adresses
     }
+
+    #[test]
+    fn a_plane_has_a_bounding_box() {
+        let b = Plane {}.bounds();
+        assert_eq!(b.min, Point::new(f64::NEG_INFINITY, 0., f64::NEG_INFINITY));
+        assert_eq!(b.max, Point::new(f64::INFINITY, 0., f64::INFINITY));
+    }
 }
@@ -0,0 +1,124 @@
+use crate::{
+    mathstructs::{point::Point, vector::Vector},
+    ray::{intersects::IntersectsRay, Ray},
+};
+
+use super::{bounds::BoundingBox, triangle::Triangle, Object, Shape};
+
+/// triangle with a normal on each corner. The normals get interpolated across the surface,
+/// so meshes look smooth instead of faceted.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SmoothTriangle {
+    pub triangle: Triangle,
+    pub n1: Vector,
+    pub n2: Vector,
+    pub n3: Vector,
+}
+
+impl SmoothTriangle {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(p1: Point, p2: Point, p3: Point, n1: Vector, n2: Vector, n3: Vector) -> Object {
+        Object::new(Shape::SmoothTriangle(Self::from_points(
+            p1, p2, p3, n1, n2, n3,
+        )))
+    }
+
+    pub fn from_points(
+        p1: Point,
+        p2: Point,
+        p3: Point,
+        n1: Vector,
+        n2: Vector,
+        n3: Vector,
+    ) -> Self {
+        Self {
+            triangle: Triangle::from_points(p1, p2, p3),
+            n1,
+            n2,
+            n3,
+        }
+    }
+
+    /// interpolates the corner normals with the barycentric choordinates of the hit
+    pub fn normal_at_uv(&self, u: f64, v: f64) -> Vector {
+        self.n2 * u + self.n3 * v + self.n1 * (1.0 - u - v)
+    }
+}
+
+impl IntersectsRay for SmoothTriangle {
+    fn intersect_raw(&self, ray: &Ray) -> Vec<f64> {
+        self.triangle.intersect_raw(ray)
+    }
+
+    /// without the hit's (u, v) we recalculate them from the point itself
+    fn normal_at(&self, point: Point) -> Vector {
+        let (u, v) = self.triangle.uv_at(point);
+        self.normal_at_uv(u, v)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        self.triangle.bounds()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cmp::ApproxEq,
+        mathstructs::{point::Point, vector::Vector},
+        ray::{
+            computations::Computations,
+            intersects::{Intersect, VecIntersections},
+            Ray,
+        },
+    };
+
+    use super::*;
+
+    fn default_smooth_triangle() -> Object {
+        SmoothTriangle::new(
+            Point::inew(0, 1, 0),
+            Point::inew(-1, 0, 0),
+            Point::inew(1, 0, 0),
+            Vector::inew(0, 1, 0),
+            Vector::inew(-1, 0, 0),
+            Vector::inew(1, 0, 0),
+        )
+    }
+
+    #[test]
+    fn an_intersection_with_a_smooth_triangle_stores_u_v() {
+        let tri = default_smooth_triangle();
+        let ray = Ray::new(Point::new(-0.2, 0.3, -2.), Vector::inew(0, 0, 1));
+        let mut xs = VecIntersections::new();
+        xs.intersect_add(&ray, &tri);
+        let hit = xs.hit().unwrap();
+        assert!(hit.u.apx_eq(&0.45));
+        assert!(hit.v.apx_eq(&0.25));
+    }
+
+    #[test]
+    fn a_smooth_triangle_uses_u_v_to_interpolate_the_normal() {
+        let tri = default_smooth_triangle();
+        let i = Intersect::new_with_uv(1., &tri, 0.45, 0.25);
+        let n = tri.normal_at_hit(&Point::inew(0, 0, 0), &i);
+        assert_eq!(n, Vector::new(-0.5547, 0.83205, 0.));
+    }
+
+    #[test]
+    fn preparing_the_normal_on_a_smooth_triangle() {
+        let tri = default_smooth_triangle();
+        let i = Intersect::new_with_uv(1., &tri, 0.45, 0.25);
+        let ray = Ray::new(Point::new(-0.2, 0.3, -2.), Vector::inew(0, 0, 1));
+        let comps = Computations::prepare(&i, &ray);
+        assert_eq!(comps.normal_v, Vector::new(-0.5547, 0.83205, 0.));
+    }
+
+    #[test]
+    fn normal_without_hit_matches_the_interpolated_normal() {
+        let tri = default_smooth_triangle();
+        let i = Intersect::new_with_uv(1., &tri, 0.45, 0.25);
+        let point = Point::new(-0.2, 0.3, 0.);
+        assert_eq!(tri.normal_at(&point), tri.normal_at_hit(&point, &i));
+    }
+}
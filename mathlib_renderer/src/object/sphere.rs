@@ -3,7 +3,7 @@ use crate::{
     ray::{intersects::IntersectsRay, Ray},
 };
 
-use super::{Object, Shape};
+use super::{bounds::BoundingBox, Object, Shape};
 
 /// always of radius 1 and at (0.0.0) - has no real state so not really a struct tbh
 #[derive(Debug, PartialEq)]
@@ -24,7 +24,7 @@ impl Sphere {
 
 impl IntersectsRay for Sphere {
     /// the t value of the position on the ray where the intersections happen. 0, 1, 2 possible.
-    fn intersect_raw(&self, ray: &Ray) -> Option<(f64, f64)> {
+    fn intersect_raw(&self, ray: &Ray) -> Vec<f64> {
         let sphere_to_ray = ray.origin - Point::new(0.0, 0.0, 0.0);
         let a = ray.direction.dot(&ray.direction);
         let b = 2.0 * ray.direction.dot(&sphere_to_ray);
@@ -32,18 +32,22 @@ impl IntersectsRay for Sphere {
         let disc = b * b - 4.0 * a * c;
         // if disc < 0 => ray not hitting the sphere
         if disc < 0.0 {
-            return None;
+            return vec![];
         }
         // else we hit it twice (or "twice on the same spot")
         let t1 = (-b - disc.sqrt()) / (2.0 * a);
         let t2 = (-b + disc.sqrt()) / (2.0 * a);
-        Some((t1, t2))
+        vec![t1, t2]
     }
 
     /// points perpendicular to the surface of the sphere
-    fn normal_at(point: Point) -> Vector {
+    fn normal_at(&self, point: Point) -> Vector {
         point - Point::new_origin() // .normalize() not neccessary as long as we assume unit-sphere
     }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::inew(-1, -1, -1), Point::inew(1, 1, 1))
+    }
 }
 
 #[cfg(test)]
@@ -57,16 +61,16 @@ mod tests {
     fn ray_intersects_a_sphere_at_two_points() {
         let ray = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1));
         let sphere = Sphere::new();
-        let intersects = sphere.intersect_raw(&ray).unwrap();
-        assert_eq!(intersects, (4.0, 6.0));
+        let intersects = sphere.intersect_raw(&ray);
+        assert_eq!(intersects, vec![4.0, 6.0]);
     }
 
     #[test]
     fn ray_intersects_sphere_at_a_tangent() {
         let ray = Ray::new(Point::inew(0, 1, -5), Vector::inew(0, 0, 1));
         let sphere = Sphere::new();
-        let intersects = sphere.intersect_raw(&ray).unwrap();
-        assert_eq!(intersects, (5.0, 5.0));
+        let intersects = sphere.intersect_raw(&ray);
+        assert_eq!(intersects, vec![5.0, 5.0]);
     }
 
     #[test]
@@ -74,38 +78,38 @@ mod tests {
         let ray = Ray::new(Point::inew(0, 2, -5), Vector::inew(0, 0, 1));
         let sphere = Sphere::new();
         let intersects = sphere.intersect_raw(&ray);
-        assert_eq!(intersects, None);
+        assert!(intersects.is_empty());
     }
 
     #[test]
     fn ray_originates_inside_a_sphere() {
         let ray = Ray::new(Point::inew(0, 0, 0), Vector::inew(0, 0, 1));
         let sphere = Sphere::new();
-        let intersects = sphere.intersect_raw(&ray).unwrap();
-        assert_eq!(intersects, (-1.0, 1.0));
+        let intersects = sphere.intersect_raw(&ray);
+        assert_eq!(intersects, vec![-1.0, 1.0]);
     }
 
     #[test]
     fn ray_starts_after_sphere() {
         let ray = Ray::new(Point::inew(0, 0, 5), Vector::inew(0, 0, 1));
         let sphere = Sphere::new();
-        let intersects = sphere.intersect_raw(&ray).unwrap();
-        assert_eq!(intersects.0, -6.0);
-        assert_eq!(intersects.1, -4.0);
-        assert_eq!(intersects, (-6.0, -4.0));
+        let intersects = sphere.intersect_raw(&ray);
+        assert_eq!(intersects[0], -6.0);
+        assert_eq!(intersects[1], -4.0);
+        assert_eq!(intersects, vec![-6.0, -4.0]);
     }
 
     #[test]
     fn sphere_s_default_transformation_is_identity() {
         let s = Sphere::new();
-        assert_eq!(s.transformation, Matrix::new_identity());
+        assert_eq!(*s.transformation(), Matrix::new_identity());
     }
 
     #[test]
     fn changing_a_sphere_s_transformation() {
         let t = Matrix::translation_new(2.0, 3.0, 4.0);
         let s = Sphere::new().with_transform(Matrix::translation_new(2.0, 3.0, 4.0));
-        assert_eq!(s.transformation, t);
+        assert_eq!(*s.transformation(), t);
     }
 
     #[test]
@@ -113,8 +117,8 @@ mod tests {
         let r = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1));
         let s = Sphere::new().with_transform(Matrix::scaling_new(2.0, 2.0, 2.0));
         let xs = s.intersect_raw(&r);
-        assert!(xs.is_some());
-        assert_eq!(xs, Some((3.0, 7.0)));
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs, vec![3.0, 7.0]);
     }
 
     #[test]
@@ -122,8 +126,8 @@ mod tests {
         let r = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1));
         let s = Sphere::new().with_transform(Matrix::translation_new(5.0, 0.0, 0.0));
         let xs = s.intersect_raw(&r);
-        dbg!(xs);
-        assert!(!xs.is_some())
+        dbg!(&xs);
+        assert!(xs.is_empty())
     }
 
     #[test]
@@ -183,8 +187,15 @@ mod tests {
     #[test]
     fn defaults_of_a_glass_sphere() {
         let s = Sphere::new_glass_sphere();
-        assert_eq!(s.transformation, Matrix::new_identity());
+        assert_eq!(*s.transformation(), Matrix::new_identity());
         assert_eq!(s.material.transparency, 1.0);
         assert_eq!(s.material.refractive_index, 1.5);
     }
+
+    #[test]
+    fn a_sphere_has_a_bounding_box() {
+        let b = Sphere {}.bounds();
+        assert_eq!(b.min, Point::inew(-1, -1, -1));
+        assert_eq!(b.max, Point::inew(1, 1, 1));
+    }
 }
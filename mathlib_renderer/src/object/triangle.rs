@@ -0,0 +1,188 @@
+use crate::{
+    cmp::EPSILON,
+    mathstructs::{point::Point, vector::Vector},
+    ray::{intersects::IntersectsRay, Ray},
+};
+
+use super::{bounds::BoundingBox, Object, Shape};
+
+/// flat triangle between 3 points. The building block for meshes.
+/// - edges and normal get precomputed on creation
+#[derive(Debug, PartialEq, Clone)]
+pub struct Triangle {
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    pub e1: Vector,
+    pub e2: Vector,
+    pub normal: Vector,
+}
+
+impl Triangle {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(p1: Point, p2: Point, p3: Point) -> Object {
+        Object::new(Shape::Triangle(Self::from_points(p1, p2, p3)))
+    }
+
+    pub fn from_points(p1: Point, p2: Point, p3: Point) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+        let normal = e2.cross(&e1).normalize();
+        Self {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal,
+        }
+    }
+
+    /// Möller–Trumbore algorithm. Returns (t, u, v) with u and v the barycentric choordinates of the hit.
+    /// - u is the weight towards p2, v the weight towards p3
+    pub fn intersect_uv(&self, ray: &Ray) -> Option<(f64, f64, f64)> {
+        let dir_cross_e2 = ray.direction.cross(&self.e2);
+        let det = self.e1.dot(&dir_cross_e2);
+        // ray is parallel to the triangle
+        if det.abs() < EPSILON {
+            return None;
+        }
+
+        let f = 1.0 / det;
+        let p1_to_origin = ray.origin - self.p1;
+        let u = f * p1_to_origin.dot(&dir_cross_e2);
+        // ray misses over the p1-p3 edge
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let origin_cross_e1 = p1_to_origin.cross(&self.e1);
+        let v = f * ray.direction.dot(&origin_cross_e1);
+        // ray misses over the p1-p2 or p2-p3 edge
+        if v < 0.0 || (u + v) > 1.0 {
+            return None;
+        }
+
+        let t = f * self.e2.dot(&origin_cross_e1);
+        Some((t, u, v))
+    }
+
+    /// barycentric choordinates (u, v) of a point that lies on the triangle
+    pub fn uv_at(&self, point: Point) -> (f64, f64) {
+        let p1_to_point = point - self.p1;
+        let d00 = self.e1.dot(&self.e1);
+        let d01 = self.e1.dot(&self.e2);
+        let d11 = self.e2.dot(&self.e2);
+        let d20 = p1_to_point.dot(&self.e1);
+        let d21 = p1_to_point.dot(&self.e2);
+        let denom = d00 * d11 - d01 * d01;
+        let u = (d11 * d20 - d01 * d21) / denom;
+        let v = (d00 * d21 - d01 * d20) / denom;
+        (u, v)
+    }
+}
+
+impl IntersectsRay for Triangle {
+    fn intersect_raw(&self, ray: &Ray) -> Vec<f64> {
+        self.intersect_uv(ray)
+            .map(|(t, _, _)| t)
+            .into_iter()
+            .collect()
+    }
+
+    // a flat triangle has the same normal everywhere
+    fn normal_at(&self, _point: Point) -> Vector {
+        self.normal
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        bounds.add_point(self.p1);
+        bounds.add_point(self.p2);
+        bounds.add_point(self.p3);
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mathstructs::{point::Point, vector::Vector},
+        ray::Ray,
+    };
+
+    use super::*;
+
+    fn default_triangle() -> Triangle {
+        Triangle::from_points(
+            Point::inew(0, 1, 0),
+            Point::inew(-1, 0, 0),
+            Point::inew(1, 0, 0),
+        )
+    }
+
+    #[test]
+    fn constructing_a_triangle() {
+        let t = default_triangle();
+        assert_eq!(t.p1, Point::inew(0, 1, 0));
+        assert_eq!(t.p2, Point::inew(-1, 0, 0));
+        assert_eq!(t.p3, Point::inew(1, 0, 0));
+        assert_eq!(t.e1, Vector::inew(-1, -1, 0));
+        assert_eq!(t.e2, Vector::inew(1, -1, 0));
+        assert_eq!(t.normal, Vector::inew(0, 0, -1));
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_triangle() {
+        let t = default_triangle();
+        assert_eq!(t.normal_at(Point::new(0., 0.5, 0.)), t.normal);
+        assert_eq!(t.normal_at(Point::new(-0.5, 0.75, 0.)), t.normal);
+        assert_eq!(t.normal_at(Point::new(0.5, 0.25, 0.)), t.normal);
+    }
+
+    fn expect_miss(origin: Point, direction: Vector) {
+        let ray = Ray::new(origin, direction);
+        assert!(default_triangle().intersect_raw(&ray).is_empty());
+    }
+
+    #[test]
+    fn intersecting_a_ray_parallel_to_the_triangle() {
+        expect_miss(Point::inew(0, -1, -2), Vector::inew(0, 1, 0));
+    }
+
+    #[test]
+    fn a_ray_misses_the_edges() {
+        // p1-p3 edge
+        expect_miss(Point::new(1., 1., -2.), Vector::inew(0, 0, 1));
+        // p1-p2 edge
+        expect_miss(Point::new(-1., 1., -2.), Vector::inew(0, 0, 1));
+        // p2-p3 edge
+        expect_miss(Point::new(0., -1., -2.), Vector::inew(0, 0, 1));
+    }
+
+    #[test]
+    fn a_ray_strikes_a_triangle() {
+        let ray = Ray::new(Point::new(0., 0.5, -2.), Vector::inew(0, 0, 1));
+        let xs = default_triangle().intersect_raw(&ray);
+        assert_eq!(xs, vec![2.0]);
+    }
+
+    #[test]
+    fn barycentric_choordinates_of_a_point_match_the_intersection() {
+        let t = default_triangle();
+        let ray = Ray::new(Point::new(-0.2, 0.3, -2.), Vector::inew(0, 0, 1));
+        let (hit, u, v) = t.intersect_uv(&ray).unwrap();
+        assert_eq!(t.uv_at(ray.position(hit)), (u, v));
+    }
+
+    #[test]
+    fn a_triangle_has_a_bounding_box() {
+        let t = Triangle::from_points(
+            Point::inew(-3, 7, 2),
+            Point::inew(6, 2, -4),
+            Point::inew(2, -1, -1),
+        );
+        assert_eq!(t.bounds().min, Point::inew(-3, -1, -4));
+        assert_eq!(t.bounds().max, Point::inew(6, 7, 2));
+    }
+}
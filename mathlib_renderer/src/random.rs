@@ -0,0 +1,60 @@
+/// tiny pseudo random number generator (SplitMix64). We only need "random enough" numbers for jittering
+/// samples, so there is no need to pull in a dependency for that.
+/// - deterministic: the same seed always produces the same sequence. So renders stay reproducible.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// seeds the generator from some floats (like the choordinates of a point)
+    pub fn from_f64s(values: &[f64]) -> Self {
+        let seed = values.iter().fold(0x9E37_79B9_7F4A_7C15_u64, |acc, v| {
+            acc.rotate_left(21) ^ v.to_bits()
+        });
+        Self::new(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        // the upper 53 bits fit exactly into the mantissa of a f64
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_produces_the_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn floats_are_between_zero_and_one() {
+        let mut rng = Rng::from_f64s(&[1.5, -2.0, 0.25]);
+        let values: Vec<f64> = (0..1000).map(|_| rng.next_f64()).collect();
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+        // roughly uniform
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!((mean - 0.5).abs() < 0.05);
+    }
+}
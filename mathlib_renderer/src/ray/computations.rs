@@ -118,7 +118,7 @@ impl Computations {
     ) -> (Point, Vector, Vector, bool, Point, Point, Vector) {
         let point = ray.position(intersection.t);
         let eye_v = -ray.direction;
-        let mut normal_v = intersection.object.normal_at(&point);
+        let mut normal_v = intersection.object.normal_at_hit(&point, intersection);
 
         let hit_is_inside_object = if normal_v.dot(&eye_v) < 0.0 {
             normal_v = -normal_v;
@@ -214,7 +214,7 @@ mod tests {
     fn the_hit_should_offset_overpoint_by_epsilon() {
         let r = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1));
         let mut shape = Sphere::new();
-        shape.transformation = Matrix::translation_new(0.0, 0.0, 1.0);
+        shape.set_transform(Matrix::translation_new(0.0, 0.0, 1.0));
         let i = Intersect::new(5.0, &shape);
         let comps = Computations::prepare(&i, &r);
         assert!(comps.over_point.z < -EPSILON / 2.0);
@@ -279,10 +279,7 @@ mod tests {
         let ray = Ray::new(Point::new(0., 0., -5.), Vector::new(0., 0., 1.));
         let shape = Sphere::new_glass_sphere().with_transform(Matrix::translation_new(0., 0., 1.));
         let xs = VecIntersections::new();
-        let i = Intersect {
-            t: 5.,
-            object: &shape,
-        };
+        let i = Intersect::new(5., &shape);
         let comps = Computations::prepare_computations(&i, &ray, &xs);
         assert!(comps.under_point.z > EPSILON / 2.);
         assert!(comps.point.z < comps.under_point.z);
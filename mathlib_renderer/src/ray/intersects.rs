@@ -2,15 +2,18 @@ use std::cmp::Ordering;
 
 use crate::{
     mathstructs::{point::Point, vector::Vector},
-    object::Object,
+    object::{bounds::BoundingBox, Object},
 };
 
 use super::Ray;
 
 /// the interface we use for all objects that our rays can hit/intersect with
 pub trait IntersectsRay {
-    fn intersect_raw(&self, ray: &Ray) -> Option<(f64, f64)>;
-    fn normal_at(point: Point) -> Vector;
+    /// the t values of all positions on the ray where it hits the shape. (unsorted)
+    fn intersect_raw(&self, ray: &Ray) -> Vec<f64>;
+    fn normal_at(&self, point: Point) -> Vector;
+    /// axis aligned box in object space that fits around the whole shape
+    fn bounds(&self) -> BoundingBox;
 }
 
 /// keeps reference to intersections our rays we cast find
@@ -18,13 +21,20 @@ pub trait IntersectsRay {
 pub struct Intersect<'a> {
     pub t: f64,
     pub object: &'a Object,
+    /// barycentric choordinates of the hit on a triangle (0 for all other shapes)
+    pub u: f64,
+    pub v: f64,
 }
 
 impl<'a> Eq for Intersect<'a> {} // cant use derive macro this will just use PartialEq for Eq
 
 impl<'a> Intersect<'a> {
     pub fn new(t: f64, object: &'a Object) -> Self {
-        Self { t, object }
+        Self::new_with_uv(t, object, 0.0, 0.0)
+    }
+
+    pub fn new_with_uv(t: f64, object: &'a Object, u: f64, v: f64) -> Self {
+        Self { t, object, u, v }
     }
 }
 
@@ -86,7 +96,7 @@ impl<'a> VecIntersections<'a> {
     }
 
     /// since we keep our vec sorted when inserting this should be sorted aswell
-    pub fn iter(&self) -> impl Iterator<Item = &Intersect> {
+    pub fn iter(&self) -> impl Iterator<Item = &Intersect<'_>> {
         self.0.iter()
     }
 
@@ -107,20 +117,12 @@ impl<'a> VecIntersections<'a> {
     //         .filter(|f| f.t.is_sign_positive())
     // }
 
-    /// adds a possible intersection to the collection
-    fn intersections(&mut self, intersect: Option<(f64, f64)>, obj: &'a Object) {
-        match intersect {
-            None => {}
-            Some((t1, t2)) => {
-                self.push(Intersect::new(t1, obj));
-                self.push(Intersect::new(t2, obj)); // TODO: we could not remove double in case of tangent? Alt use hashmap.
-            }
-        }
-    }
-
     /// calculates intersection then adds to collection
+    /// - for groups the intersections reference the child that got hit
     pub fn intersect_add(&mut self, ray: &Ray, obj: &'a Object) -> &Self {
-        self.intersections(obj.intersect_raw(ray), obj);
+        for intersect in obj.intersect(ray) {
+            self.push(intersect);
+        }
         self
     }
 }
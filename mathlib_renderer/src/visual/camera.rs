@@ -1,11 +1,30 @@
-use std::{f64::consts::PI, time::Instant};
+use std::f64::consts::PI;
 
 use crate::{
     mathstructs::{matrix::Matrix, point::Point},
+    random::Rng,
     ray::Ray,
 };
 
-use super::{canvas::Canvas, world::World};
+use super::{
+    canvas::Canvas,
+    render::{RenderSettings, Renderer},
+    world::World,
+};
+
+/// how the pixels of the image get mapped to rays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// the usual pinhole (or thin-lens) camera
+    Perspective,
+    /// parallel rays, no perspective. view_width is how many world units fit into the image width.
+    Orthographic { view_width: f64 },
+    /// 360° x 180° panorama (like for VR previews). The image should be twice as wide as high.
+    Equirectangular,
+    /// equidistant fisheye. The field_of_view is the angle across the whole image width
+    /// and can go up to 2 PI.
+    Fisheye,
+}
 
 #[derive(Debug)]
 pub struct Camera {
@@ -14,10 +33,18 @@ pub struct Camera {
     /// heigth in pixels
     pub height: usize,
     pub field_of_view: f64,
-    pub transform: Matrix,
+    /// set it with set_transform() or with_transform() so the cached inverse gets updated
+    transform: Matrix,
+    /// cached inverse of the transform, needed for every single ray
+    inverse: Matrix,
     pub pixel_size: f64,
     pub half_width: f64,
     pub half_height: f64,
+    /// diameter of the lens. 0 is a pinhole camera where everything is in focus.
+    pub aperture: f64,
+    /// distance from the camera to the plane that is perfectly in focus
+    pub focal_distance: f64,
+    pub projection: Projection,
 }
 
 impl Default for Camera {
@@ -43,80 +70,143 @@ impl Camera {
             height,
             field_of_view: fow,
             transform: Matrix::new_identity(),
+            inverse: Matrix::new_identity(),
             pixel_size,
             half_width,
             half_height,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            projection: Projection::Perspective,
+        }
+    }
+
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        if let Projection::Orthographic { view_width } = projection {
+            assert!(view_width > 0.0, "orthographic view width must be positive");
         }
+        self.projection = projection;
+        self
+    }
+
+    /// thin-lens camera. Only things at the focal_distance are sharp, the bigger the aperture
+    /// the blurrier everything else gets. Needs many samples per pixel to not look noisy.
+    /// - only used by the perspective projection
+    pub fn with_depth_of_field(mut self, aperture: f64, focal_distance: f64) -> Self {
+        assert!(aperture >= 0.0, "aperture can not be negative");
+        assert!(focal_distance > 0.0, "focal distance must be positive");
+        self.aperture = aperture;
+        self.focal_distance = focal_distance;
+        self
     }
 
     pub fn with_transform(mut self, t: Matrix) -> Self {
-        self.transform = t;
+        self.set_transform(t);
         self
     }
 
+    pub fn set_transform(&mut self, t: Matrix) {
+        self.transform = t;
+        self.inverse = t.inverse();
+    }
+
+    pub fn transform(&self) -> &Matrix {
+        &self.transform
+    }
+
     /// for each canvas pixel get the correspoinding world_choordinates and then the ray from it
     pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
-        let offset_x = (px as f64 + 0.5) * self.pixel_size;
-        let offset_y = (py as f64 + 0.5) * self.pixel_size;
+        self.ray_for_subpixel(px, py, 0.5, 0.5)
+    }
+
+    /// ray through a point inside the pixel. dx and dy in [0, 1), (0.5, 0.5) is the center.
+    pub fn ray_for_subpixel(&self, px: usize, py: usize, dx: f64, dy: f64) -> Ray {
+        let (origin, target) = match self.projection {
+            Projection::Perspective => self.perspective_ray(px, py, dx, dy),
+            Projection::Orthographic { view_width } => {
+                let pixel_size = view_width / self.width as f64;
+                let half_width = view_width / 2.0;
+                let half_height = pixel_size * self.height as f64 / 2.0;
+                let x = half_width - (px as f64 + dx) * pixel_size;
+                let y = half_height - (py as f64 + dy) * pixel_size;
+                (Point::new(x, y, 0.0), Point::new(x, y, -1.0))
+            }
+            Projection::Equirectangular => {
+                // u and v in [0, 1] across the whole image
+                let u = (px as f64 + dx) / self.width as f64;
+                let v = (py as f64 + dy) / self.height as f64;
+                let longitude = (0.5 - u) * 2.0 * PI;
+                let latitude = (0.5 - v) * PI;
+                let direction = Point::new(
+                    longitude.sin() * latitude.cos(),
+                    latitude.sin(),
+                    -longitude.cos() * latitude.cos(),
+                );
+                (Point::new_origin(), direction)
+            }
+            Projection::Fisheye => {
+                // distance from the image center, 1 at the left and right edge
+                let half = self.width as f64 / 2.0;
+                let x = (half - (px as f64 + dx)) / half;
+                let y = (self.height as f64 / 2.0 - (py as f64 + dy)) / half;
+                let r = (x * x + y * y).sqrt();
+                let theta = r * self.field_of_view / 2.0;
+                let azimuth = y.atan2(x);
+                let direction = Point::new(
+                    theta.sin() * azimuth.cos(),
+                    theta.sin() * azimuth.sin(),
+                    -theta.cos(),
+                );
+                (Point::new_origin(), direction)
+            }
+        };
+        // using the camera matrix transform the canvas point and the origin
+        // and then compute the ray's directin vector;
+        let origin = self.inverse * origin;
+        let target = self.inverse * target;
+        Ray::new(origin, (target - origin).normalize())
+    }
+
+    /// (origin, point on the canvas) of a ray in camera space
+    fn perspective_ray(&self, px: usize, py: usize, dx: f64, dy: f64) -> (Point, Point) {
+        let offset_x = (px as f64 + dx) * self.pixel_size;
+        let offset_y = (py as f64 + dy) * self.pixel_size;
 
         let world_x = self.half_width - offset_x;
         let world_y = self.half_height - offset_y;
 
-        // using the camera matrix transform the canvas point and the origin
-        // and then compute the ray's directin vector;
-        let inverse = self.transform.inverse();
-        let pixel = inverse * Point::new(world_x, world_y, -1.0);
-        let origin = inverse * Point::new(0.0, 0.0, 0.0);
-        let direction = (pixel - origin).normalize();
-        Ray::new(origin, direction)
+        if self.aperture <= 0.0 {
+            return (Point::new_origin(), Point::new(world_x, world_y, -1.0));
+        }
+        // thin lens: all rays of this point of the pixel meet again on the focal plane,
+        // but start from a different point on the lens.
+        let fd = self.focal_distance;
+        let (lens_x, lens_y) = self.point_on_lens(px, py, dx, dy);
+        (
+            Point::new(lens_x, lens_y, 0.0),
+            Point::new(world_x * fd, world_y * fd, -fd),
+        )
+    }
+
+    /// random point on the lens disk (in camera space). Seeded by the sample, so it stays reproducible.
+    /// The seed is where the sample is in the whole image, so a corner two pixels share
+    /// (dx = 1 of one, dx = 0 of the next) gets the same point on the lens from both.
+    fn point_on_lens(&self, px: usize, py: usize, dx: f64, dy: f64) -> (f64, f64) {
+        let mut rng = Rng::from_f64s(&[px as f64 + dx, py as f64 + dy]);
+        // sqrt so the points are spread evenly over the disk, instead of bunching up in the center
+        let radius = self.aperture / 2.0 * rng.next_f64().sqrt();
+        let angle = 2.0 * PI * rng.next_f64();
+        (radius * angle.cos(), radius * angle.sin())
     }
 
     /// for given camera and world we render out the pixels to a canvas
-    pub fn render(&self, world: World) -> Canvas {
-        let mut canvas = Canvas::new(self.width, self.height);
-        canvas.arr.iter_mut().enumerate().for_each(|(y, row)| {
-            row.iter_mut().enumerate().for_each(|(x, col)| {
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(&ray, 1);
-                *col = color;
-            });
-        });
-
-        canvas
-    }
-
-    pub fn render_with_progress_procedural(&self, world: World) -> Canvas {
-        let now = Instant::now();
-        let mut canvas = Canvas::new(self.width, self.height);
-        let mut nxt_percent = (1, self.height / 10, "::".to_string());
-        for (y, row) in canvas.arr.iter_mut().enumerate() {
-            if y == nxt_percent.1 {
-                println!(
-                    "{} {}0 % took: {}s",
-                    nxt_percent.2,
-                    nxt_percent.0,
-                    now.elapsed().as_secs()
-                );
-                nxt_percent = (
-                    nxt_percent.0 + 1,
-                    (nxt_percent.0 + 1) * self.height / 10,
-                    nxt_percent.2 + "::",
-                );
-            }
-            for (x, col) in row.iter_mut().enumerate() {
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(&ray, 1);
-                *col = color;
-            }
-        }
-        println!("total render took: {} seconds.", now.elapsed().as_secs());
-        canvas
+    pub fn render(&self, world: World, settings: &RenderSettings) -> Canvas {
+        Renderer::new(self, &world, *settings).render()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::f64::consts::PI;
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
 
     use crate::{
         cmp::ApproxEq,
@@ -133,7 +223,7 @@ mod tests {
         assert_eq!(c.width, 160);
         assert_eq!(c.height, 120);
         assert_eq!(c.field_of_view, PI / 2.0);
-        assert_eq!(c.transform, Matrix::new_identity())
+        assert_eq!(c.transform(), &Matrix::new_identity())
     }
 
     #[test]
@@ -165,7 +255,7 @@ mod tests {
     #[test]
     fn constructing_a_ray_when_camera_is_transformed() {
         let mut c = Camera::new(201, 101, PI / 2.0);
-        c.transform = Matrix::rotation_y_new(PI / 4.0) * Matrix::translation_new(0.0, -2.0, 5.0);
+        c.set_transform(Matrix::rotation_y_new(PI / 4.0) * Matrix::translation_new(0.0, -2.0, 5.0));
         let r = c.ray_for_pixel(100, 50);
         assert_eq!(r.origin, Point::inew(0, 2, -5));
         let sq = 2.0_f64.sqrt() / 2.0;
@@ -180,7 +270,83 @@ mod tests {
         let up = Vector::inew(0, 1, 0);
         let c =
             Camera::new(11, 11, PI / 2.0).with_transform(Matrix::view_transform_new(from, to, up));
-        let image = c.render(w);
+        let image = c.render(w, &RenderSettings::default());
         assert_eq!(image[5][5], Col::new(0.38066, 0.47583, 0.2855));
     }
+
+    // depth of field
+    #[test]
+    fn a_camera_without_aperture_is_a_pinhole() {
+        let c = Camera::new(201, 101, PI / 2.0).with_depth_of_field(0.0, 5.0);
+        let r = c.ray_for_pixel(0, 0);
+        assert_eq!(r.origin, Point::inew(0, 0, 0));
+        assert_eq!(r.direction, Vector::new(0.66519, 0.33259, -0.66851));
+    }
+
+    #[test]
+    fn lens_rays_meet_on_the_focal_plane() {
+        let c = Camera::new(201, 101, PI / 2.0)
+            .with_transform(Matrix::translation_new(0.0, -2.0, 5.0))
+            .with_depth_of_field(0.5, 4.0);
+        let pinhole = Camera::new(201, 101, PI / 2.0).with_transform(*c.transform());
+        for (dx, dy) in [(0.1, 0.2), (0.5, 0.5), (0.9, 0.3)] {
+            let r = c.ray_for_subpixel(30, 60, dx, dy);
+            let p = pinhole.ray_for_subpixel(30, 60, dx, dy);
+            // starts somewhere on the lens (camera sits at (0, 2, -5) looking at -z)
+            assert!(r.origin.z.apx_eq(&-5.0));
+            let (lx, ly) = (r.origin.x, r.origin.y - 2.0);
+            assert!((lx * lx + ly * ly).sqrt() <= 0.25);
+            // but hits the same point on the focal plane (z = -9) as the pinhole ray
+            let t = (-9.0 - r.origin.z) / r.direction.z;
+            let pt = (-9.0 - p.origin.z) / p.direction.z;
+            assert_eq!(r.position(t), p.position(pt));
+        }
+        // different samples get different points on the lens
+        assert_ne!(
+            c.ray_for_subpixel(30, 60, 0.1, 0.2).origin,
+            c.ray_for_subpixel(30, 60, 0.9, 0.3).origin
+        );
+    }
+
+    // projections
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let c = Camera::new(200, 100, PI / 2.0)
+            .with_transform(Matrix::translation_new(0.0, -2.0, 5.0))
+            .with_projection(Projection::Orthographic { view_width: 10.0 });
+        let center = c.ray_for_subpixel(100, 50, 0.0, 0.0);
+        assert_eq!(center.origin, Point::inew(0, 2, -5));
+        assert_eq!(center.direction, Vector::inew(0, 0, -1));
+        let corner = c.ray_for_subpixel(0, 0, 0.0, 0.0);
+        assert_eq!(corner.origin, Point::new(5., 4.5, -5.));
+        assert_eq!(corner.direction, Vector::inew(0, 0, -1));
+    }
+
+    #[test]
+    fn equirectangular_covers_all_directions() {
+        let c = Camera::new(200, 100, PI / 2.0).with_projection(Projection::Equirectangular);
+        let dir = |px, py| c.ray_for_subpixel(px, py, 0.0, 0.0).direction;
+        assert_eq!(dir(100, 50), Vector::inew(0, 0, -1));
+        // same side as pixels on the left of a perspective camera
+        assert_eq!(dir(50, 50), Vector::inew(1, 0, 0));
+        assert_eq!(dir(150, 50), Vector::inew(-1, 0, 0));
+        assert_eq!(dir(0, 50), Vector::inew(0, 0, 1));
+        assert_eq!(dir(100, 0), Vector::inew(0, 1, 0));
+        assert_eq!(
+            c.ray_for_subpixel(0, 0, 0.0, 0.0).origin,
+            Point::new_origin()
+        );
+    }
+
+    #[test]
+    fn fisheye_angle_grows_with_the_distance_to_the_center() {
+        let c = Camera::new(200, 100, PI).with_projection(Projection::Fisheye);
+        let dir = |px, py| c.ray_for_subpixel(px, py, 0.0, 0.0).direction;
+        assert_eq!(dir(100, 50), Vector::inew(0, 0, -1));
+        // the edges of a 180° fisheye look sideways
+        assert_eq!(dir(0, 50), Vector::inew(1, 0, 0));
+        assert_eq!(dir(200, 50), Vector::inew(-1, 0, 0));
+        // halfway to the edge is 45°
+        assert_eq!(dir(100, 0), Vector::new(0., FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+    }
 }
@@ -11,7 +11,7 @@ use super::color::{Col, BLACK};
 // / | 1,0  | 1,1 | 1,2 |
 // / | 2,0  | 2,1 | 2,2 |
 // / ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Canvas {
     pub width: usize,
     pub height: usize,
@@ -38,6 +38,82 @@ impl Canvas {
     pub fn read(&self, x: usize, y: usize) -> &Col {
         &self[y][x]
     }
+
+    /// copies the other canvas (like a rendered tile) into this one, with its top left at x0, y0.
+    /// Whatever does not fit gets cut off.
+    pub fn paste(&mut self, x0: usize, y0: usize, block: &Canvas) -> &mut Self {
+        if x0 >= self.width || y0 >= self.height {
+            return self;
+        }
+        let width = block.width.min(self.width.saturating_sub(x0));
+        for (row, y) in block.arr.iter().zip(y0..self.height) {
+            self.arr[y][x0..x0 + width].copy_from_slice(&row[..width]);
+        }
+        self
+    }
+
+    /// puts the blocks (x0, y0, pixels) together into one canvas of the given size
+    pub fn stitch(
+        width: usize,
+        height: usize,
+        blocks: impl IntoIterator<Item = (usize, usize, Canvas)>,
+    ) -> Self {
+        let mut canvas = Canvas::new(width, height);
+        for (x0, y0, block) in blocks {
+            canvas.paste(x0, y0, &block);
+        }
+        canvas
+    }
+}
+
+/// sums up the images of multiple render passes (see Renderer::render_progressive).
+/// The current image is the average of all passes.
+#[derive(Debug, Clone)]
+pub struct Accumulator {
+    pub width: usize,
+    pub height: usize,
+    sum: Vec<Vec<Col>>,
+    passes: usize,
+}
+
+impl Accumulator {
+    pub fn new(w: usize, h: usize) -> Self {
+        Accumulator {
+            width: w,
+            height: h,
+            sum: vec![vec![BLACK; w]; h],
+            passes: 0,
+        }
+    }
+
+    pub fn add(&mut self, pass: &Canvas) -> &mut Self {
+        assert_eq!((pass.width, pass.height), (self.width, self.height));
+        for (sum_row, row) in self.sum.iter_mut().zip(&pass.arr) {
+            for (sum, col) in sum_row.iter_mut().zip(row) {
+                *sum = *sum + *col;
+            }
+        }
+        self.passes += 1;
+        self
+    }
+
+    pub fn passes(&self) -> usize {
+        self.passes
+    }
+
+    /// the average of all passes so far. Black without any.
+    pub fn image(&self) -> Canvas {
+        let factor = 1.0 / self.passes.max(1) as f64;
+        Canvas {
+            width: self.width,
+            height: self.height,
+            arr: self
+                .sum
+                .iter()
+                .map(|row| row.iter().map(|col| *col * factor).collect())
+                .collect(),
+        }
+    }
 }
 
 impl Index<usize> for Canvas {
@@ -102,6 +178,50 @@ mod tests {
         assert_eq!(canvas[5][5], WHITE);
     }
 
+    #[test]
+    fn pasting_a_smaller_canvas() {
+        let mut block = Canvas::new(3, 2);
+        block[0][0] = WHITE;
+        block[1][2] = WHITE;
+        let mut canvas = Canvas::new(10, 20);
+        canvas.paste(4, 5, &block);
+        assert_eq!(canvas[5][4], WHITE);
+        assert_eq!(canvas[6][6], WHITE);
+        assert_eq!(canvas[5][5], BLACK);
+        // overlaps the bottom right corner
+        canvas.paste(9, 19, &block);
+        assert_eq!(canvas[19][9], WHITE);
+    }
+
+    #[test]
+    fn pasting_outside_of_the_canvas_does_nothing() {
+        let mut block = Canvas::new(3, 2);
+        block[0][0] = WHITE;
+        let mut canvas = Canvas::new(10, 20);
+        canvas
+            .paste(10, 0, &block)
+            .paste(12, 3, &block)
+            .paste(0, 20, &block);
+        canvas.paste(50, 50, &block);
+        assert_eq!(canvas, Canvas::new(10, 20));
+    }
+
+    #[test]
+    fn accumulating_passes_averages_them() {
+        let mut acc = Accumulator::new(2, 1);
+        assert_eq!(acc.image()[0][0], BLACK);
+        let mut pass = Canvas::new(2, 1);
+        pass[0][0] = WHITE;
+        acc.add(&pass);
+        pass[0][0] = Col::new(0.5, 0., 0.);
+        pass[0][1] = Col::new(0., 0., 1.);
+        acc.add(&pass);
+        let image = acc.image();
+        assert_eq!(acc.passes(), 2);
+        assert_eq!(image[0][0], Col::new(0.75, 0.5, 0.5));
+        assert_eq!(image[0][1], Col::new(0., 0., 0.5));
+    }
+
     #[test]
     fn read_pixel() {
         let (w, h) = (10, 20);
@@ -7,6 +7,7 @@ use crate::{cmp::ApproxEq, io::ppm::COLOR_MAXVAL};
 
 /// represents a RGB-Color
 ///  - final colors should be between 0 - 1.
+///
 /// But intermediate ones used for further calculating might exceede those borders in both directions
 #[derive(Debug, Clone, Copy)]
 pub struct Col {
@@ -19,6 +20,14 @@ impl Col {
     pub fn new(r: f64, g: f64, b: f64) -> Self {
         Self { r, g, b }
     }
+
+    /// biggest difference in any of the channels. Used to find out how different 2 colors look.
+    pub fn max_difference(&self, other: &Col) -> f64 {
+        (self.r - other.r)
+            .abs()
+            .max((self.g - other.g).abs())
+            .max((self.b - other.b).abs())
+    }
 }
 
 impl Display for Col {
@@ -177,4 +186,12 @@ mod tests {
         assert_eq!(l * 2.0, e);
         assert_eq!(0.5 * e, l);
     }
+
+    #[test]
+    fn max_difference_of_colors() {
+        let a = Col::new(0.2, 0.5, 1.0);
+        let b = Col::new(0.3, 0.1, 0.9);
+        assert!(a.max_difference(&b).apx_eq(&0.4));
+        assert_eq!(a.max_difference(&a), 0.0);
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{
     mathstructs::{point::Point, vector::Vector},
     object::Object,
+    random::Rng,
 };
 
 use super::{
@@ -9,17 +10,314 @@ use super::{
     patterns::Texture,
 };
 
+/// all the different kinds of light sources a world can have
 #[derive(Debug, Clone, PartialEq)]
-pub struct Light {
+pub enum Light {
+    Point(PointLight),
+    Area(AreaLight),
+    Spot(SpotLight),
+    Directional(DirectionalLight),
+}
+
+/// how the light gets weaker the further away from it we are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Attenuation {
+    /// same intensity at any distance
+    None,
+    /// 1 / (constant + linear * distance + quadratic * distance^2)
+    Polynomial {
+        constant: f64,
+        linear: f64,
+        quadratic: f64,
+    },
+    /// 1 / distance^2, like real lights. Needs way brighter intensities than the other ones.
+    InverseSquare,
+}
+
+impl Attenuation {
+    /// factor the light's intensity gets multiplied with at the distance.
+    /// Where there is nothing to divide by (like [ 0, 0, 0 ] or right at the light) it is 1,
+    /// instead of inf or NaN ending up in the image.
+    pub fn factor(&self, distance: f64) -> f64 {
+        let divisor = match *self {
+            Attenuation::None => return 1.0,
+            Attenuation::Polynomial {
+                constant,
+                linear,
+                quadratic,
+            } => constant + linear * distance + quadratic * distance * distance,
+            Attenuation::InverseSquare => distance * distance,
+        };
+        match divisor > 0.0 {
+            true => 1.0 / divisor,
+            false => 1.0,
+        }
+    }
+}
+
+/// light that shines from a single point in every direction
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointLight {
     pub position: Point,
     pub intensity: Col,
+    pub attenuation: Attenuation,
+}
+
+/// rectangular area light that gets sampled on a grid of usteps * vsteps cells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AreaLight {
+    /// the center of the rectangle
+    pub position: Point,
+    pub intensity: Col,
+    /// gets applied to each sample with its own distance
+    pub attenuation: Attenuation,
+    pub corner: Point,
+    /// edge of a single cell in u direction (the full edge divided by usteps)
+    pub uvec: Vector,
+    pub usteps: usize,
+    /// edge of a single cell in v direction (the full edge divided by vsteps)
+    pub vvec: Vector,
+    pub vsteps: usize,
+    /// randomly moves each sample inside its cell, instead of using the cell's center.
+    /// This trades the banding of the soft shadows for noise.
+    pub jitter: bool,
+}
+
+/// point light that only shines inside a cone around its direction.
+/// - full intensity inside the inner_angle, then fading out till the outer_angle
+/// - both angles are in radians, measured from the direction to the edge of the cone
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpotLight {
+    pub position: Point,
+    /// normalized direction the spot points at
+    pub direction: Vector,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub intensity: Col,
+    pub attenuation: Attenuation,
+}
+
+/// light that is infinitely far away, like the sun. All its rays are parallel
+/// and it does not matter how far away the lit point is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectionalLight {
+    /// normalized direction the light travels in
+    pub direction: Vector,
+    pub intensity: Col,
+}
+
+/// a single point (or direction) we sample a light at, as seen from the point that gets lit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightSample {
+    /// normalized vector from the lit point towards the light
+    pub direction: Vector,
+    /// how far away the light is. Infinite for directional lights.
+    pub distance: f64,
+}
+
+impl LightSample {
+    /// sample of a light sitting at the point `to`
+    pub fn towards(from: &Point, to: &Point) -> Self {
+        let v = *to - *from;
+        LightSample {
+            direction: v.normalize(),
+            distance: v.magnitude(),
+        }
+    }
+}
+
+impl AreaLight {
+    /// rectangle spanned by full_uvec and full_vvec from the corner
+    pub fn new(
+        corner: Point,
+        full_uvec: Vector,
+        usteps: usize,
+        full_vvec: Vector,
+        vsteps: usize,
+        intensity: Col,
+        jitter: bool,
+    ) -> Self {
+        assert!(usteps > 0 && vsteps > 0, "area light needs at least 1 step");
+        AreaLight {
+            position: corner + full_uvec * 0.5 + full_vvec * 0.5,
+            intensity,
+            attenuation: Attenuation::None,
+            corner,
+            uvec: full_uvec * (1.0 / usteps as f64),
+            usteps,
+            vvec: full_vvec * (1.0 / vsteps as f64),
+            vsteps,
+            jitter,
+        }
+    }
+
+    /// number of points we sample the light at
+    pub fn samples(&self) -> usize {
+        self.usteps * self.vsteps
+    }
+
+    /// point inside the cell (u, v) of the light. The center of the cell unless jittered.
+    pub fn point_on_light(&self, u: usize, v: usize, rng: &mut Rng) -> Point {
+        let (ju, jv) = match self.jitter {
+            true => (rng.next_f64(), rng.next_f64()),
+            false => (0.5, 0.5),
+        };
+        self.corner + self.uvec * (u as f64 + ju) + self.vvec * (v as f64 + jv)
+    }
+
+    /// all the points we sample the light at, when lighting the given point.
+    /// - the jitter is seeded by the point, so the same point always gets the same samples
+    pub fn sample_points(&self, point: &Point) -> Vec<Point> {
+        let mut rng = Rng::from_f64s(&[point.x, point.y, point.z]);
+        (0..self.vsteps)
+            .flat_map(|v| (0..self.usteps).map(move |u| (u, v)))
+            .map(|(u, v)| self.point_on_light(u, v, &mut rng))
+            .collect()
+    }
+}
+
+impl SpotLight {
+    /// 1 inside the inner cone, 0 outside the outer cone and a smooth falloff in between
+    pub fn cone_factor(&self, point: &Point) -> f64 {
+        let cos_angle = (*point - self.position).normalize().dot(&self.direction);
+        let (cos_inner, cos_outer) = (self.inner_angle.cos(), self.outer_angle.cos());
+        if cos_angle >= cos_inner {
+            return 1.0;
+        }
+        if cos_angle <= cos_outer {
+            return 0.0;
+        }
+        // smoothstep
+        let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
 }
 
 impl Light {
     pub fn new_point_light(position: Point, intensity: Col) -> Self {
-        Light {
+        Light::Point(PointLight {
+            position,
+            intensity,
+            attenuation: Attenuation::None,
+        })
+    }
+
+    /// rectangle spanned by full_uvec and full_vvec from the corner
+    pub fn new_area_light(
+        corner: Point,
+        full_uvec: Vector,
+        usteps: usize,
+        full_vvec: Vector,
+        vsteps: usize,
+        intensity: Col,
+        jitter: bool,
+    ) -> Self {
+        Light::Area(AreaLight::new(
+            corner, full_uvec, usteps, full_vvec, vsteps, intensity, jitter,
+        ))
+    }
+
+    /// angles in radians. The inner_angle has to be smaller or equal to the outer_angle.
+    pub fn new_spot_light(
+        position: Point,
+        direction: Vector,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Col,
+    ) -> Self {
+        assert!(
+            inner_angle <= outer_angle,
+            "spot light inner angle must not be bigger than the outer angle"
+        );
+        Light::Spot(SpotLight {
             position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+            attenuation: Attenuation::None,
+        })
+    }
+
+    /// direction is the way the light travels (so from the sun down to the ground)
+    pub fn new_directional_light(direction: Vector, intensity: Col) -> Self {
+        Light::Directional(DirectionalLight {
+            direction: direction.normalize(),
             intensity,
+        })
+    }
+
+    /// directional lights are infinitely far away, so they ignore the attenuation
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> Self {
+        match &mut self {
+            Light::Point(l) => l.attenuation = attenuation,
+            Light::Area(l) => l.attenuation = attenuation,
+            Light::Spot(l) => l.attenuation = attenuation,
+            Light::Directional(_) => {}
+        }
+        self
+    }
+
+    pub fn attenuation(&self) -> Attenuation {
+        match self {
+            Light::Point(l) => l.attenuation,
+            Light::Area(l) => l.attenuation,
+            Light::Spot(l) => l.attenuation,
+            Light::Directional(_) => Attenuation::None,
+        }
+    }
+
+    /// attenuation factor at the distance between the point and the light('s center)
+    pub fn attenuation_at(&self, point: &Point) -> f64 {
+        match self.position() {
+            Some(position) => self.attenuation().factor((position - *point).magnitude()),
+            None => 1.0,
+        }
+    }
+
+    pub fn intensity(&self) -> Col {
+        match self {
+            Light::Point(l) => l.intensity,
+            Light::Area(l) => l.intensity,
+            Light::Spot(l) => l.intensity,
+            Light::Directional(l) => l.intensity,
+        }
+    }
+
+    /// where the light sits (the center for area lights). Directional lights have no position.
+    pub fn position(&self) -> Option<Point> {
+        match self {
+            Light::Point(l) => Some(l.position),
+            Light::Area(l) => Some(l.position),
+            Light::Spot(l) => Some(l.position),
+            Light::Directional(_) => None,
+        }
+    }
+
+    /// all the samples of the light, as seen from the given point.
+    /// - area lights have one per cell, all others just have a single one
+    pub fn samples_from(&self, point: &Point) -> Vec<LightSample> {
+        match self {
+            Light::Point(l) => vec![LightSample::towards(point, &l.position)],
+            Light::Spot(l) => vec![LightSample::towards(point, &l.position)],
+            Light::Area(l) => l
+                .sample_points(point)
+                .iter()
+                .map(|p| LightSample::towards(point, p))
+                .collect(),
+            Light::Directional(l) => vec![LightSample {
+                direction: -l.direction,
+                distance: f64::INFINITY,
+            }],
+        }
+    }
+
+    /// fraction of the light that gets emitted towards the point (ignoring shadows).
+    /// Only spot lights dont shine in every direction.
+    pub fn falloff(&self, point: &Point) -> f64 {
+        match self {
+            Light::Spot(l) => l.cone_factor(point),
+            _ => 1.0,
         }
     }
 
@@ -31,7 +329,7 @@ impl Light {
         point: &Point,
         eye_v: &Vector,
         normal_v: &Vector,
-        in_shadow: bool,
+        light_intensity: f64,
     ) -> Col {
         let material_color = match material.pattern.texture {
             Texture::Single(col) => col,
@@ -43,7 +341,7 @@ impl Light {
             point,
             eye_v,
             normal_v,
-            in_shadow,
+            light_intensity,
             material_color,
         )
     }
@@ -56,7 +354,7 @@ impl Light {
         point: &Point,
         eye_v: &Vector,
         normal_v: &Vector,
-        in_shadow: bool,
+        light_intensity: f64,
     ) -> Col {
         let material_color = match material.pattern.texture {
             Texture::Single(col) => col,
@@ -68,33 +366,64 @@ impl Light {
             point,
             eye_v,
             normal_v,
-            in_shadow,
+            light_intensity,
             material_color,
         )
     }
 
     /// phong-reflection-model combines material and light source to shading
+    /// - light_intensity is the fraction of the light that reaches the point (0 = fully in shadow)
     fn lighting_calculations(
         material: &Material,
         light: &Light,
         point: &Point,
         eye_v: &Vector,
         normal_v: &Vector,
-        in_shadow: bool,
+        light_intensity: f64,
         material_color: Col,
     ) -> Col {
         // combine the surface color with the lights's color/intensity
-        let effective_col = material_color * light.intensity;
-        // find the direction to the light source
-        let light_v = (light.position - *point).normalize();
-        // compute the ambient contribution
-        let ambient = effective_col * material.ambient;
-
-        // when in shadow we ignore diffure & specular -> only ambient lighting left:
-        if in_shadow {
+        let effective_col = material_color * light.intensity();
+        // compute the ambient contribution. It fades with the distance too, otherwise
+        // bright (attenuated) lights would wash out everything with their ambient part
+        let ambient = effective_col * material.ambient * light.attenuation_at(point);
+
+        // points outside of a spot light's cone are lit like they are in shadow
+        let light_intensity = light_intensity * light.falloff(point);
+
+        // when fully in shadow we ignore diffure & specular -> only ambient lighting left:
+        if light_intensity <= 0.0 {
             return ambient;
         }
 
+        // area lights get the average of diffuse & specular over all their sample points
+        let samples = light.samples_from(point);
+        let attenuation = light.attenuation();
+        let mut sum = BLACK;
+        for sample in &samples {
+            // the direction to the light source
+            let light_v = sample.direction;
+            sum = sum
+                + Self::diffuse_and_specular(
+                    material,
+                    light,
+                    eye_v,
+                    normal_v,
+                    effective_col,
+                    light_v,
+                ) * attenuation.factor(sample.distance);
+        }
+        ambient + sum * (light_intensity / samples.len() as f64)
+    }
+
+    fn diffuse_and_specular(
+        material: &Material,
+        light: &Light,
+        eye_v: &Vector,
+        normal_v: &Vector,
+        effective_col: Col,
+        light_v: Vector,
+    ) -> Col {
         // light_dot_normal represents the cosine of the angle between the
         // light vector and the normal vector. A negative number means the
         // light is on the other side of the surface
@@ -120,10 +449,10 @@ impl Light {
             } else {
                 // compute the specular contribution
                 let factor = f64::powf(reflect_dot_eye, material.shininess);
-                specular = light.intensity * material.specular * factor;
+                specular = light.intensity() * material.specular * factor;
             }
         }
-        ambient + diffuse + specular
+        diffuse + specular
     }
 }
 
@@ -136,8 +465,11 @@ impl Default for Light {
 
 #[cfg(test)]
 mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
     use crate::{
         mathstructs::vector::Vector,
+        random::Rng,
         visual::{color::WHITE, patterns::Pattern},
     };
 
@@ -148,8 +480,8 @@ mod tests {
         let intensity = Col::new(1.0, 1.0, 1.0);
         let position = Point::inew(0, 0, 0);
         let light = Light::new_point_light(position, intensity);
-        assert_eq!(light.intensity, intensity);
-        assert_eq!(light.position, position);
+        assert_eq!(light.intensity(), intensity);
+        assert_eq!(light.position(), Some(position));
     }
 
     fn setup_mat_pos() -> (Material, Point) {
@@ -164,7 +496,7 @@ mod tests {
         let v_eye = Vector::inew(0, 0, -1);
         let v_normal = Vector::inew(0, 0, -1);
         let light = Light::new_point_light(Point::inew(0, 0, -10), Col::new(1.0, 1.0, 1.0));
-        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, false);
+        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, 1.0);
         assert_eq!(res, Col::new(1.9, 1.9, 1.9));
     }
 
@@ -175,7 +507,7 @@ mod tests {
         let v_eye = Vector::new(0.0, sq, -sq);
         let v_normal = Vector::inew(0, 0, -1);
         let light = Light::new_point_light(Point::inew(0, 0, -10), Col::new(1.0, 1.0, 1.0));
-        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, false);
+        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, 1.0);
         assert_eq!(res, Col::new(1.0, 1.0, 1.0));
     }
 
@@ -185,7 +517,7 @@ mod tests {
         let v_eye = Vector::inew(0, 0, -1);
         let v_normal = Vector::inew(0, 0, -1);
         let light = Light::new_point_light(Point::inew(0, 10, -10), Col::new(1.0, 1.0, 1.0));
-        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, false);
+        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, 1.0);
         assert_eq!(res, Col::new(0.7364, 0.7364, 0.7364));
     }
 
@@ -196,7 +528,7 @@ mod tests {
         let v_eye = Vector::new(0.0, -sq, -sq);
         let v_normal = Vector::inew(0, 0, -1);
         let light = Light::new_point_light(Point::inew(0, 10, -10), Col::new(1.0, 1.0, 1.0));
-        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, false);
+        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, 1.0);
         assert_eq!(res, Col::new(1.63639, 1.63639, 1.63639));
     }
 
@@ -206,7 +538,7 @@ mod tests {
         let v_eye = Vector::inew(0, 0, -1);
         let v_normal = Vector::inew(0, 0, -1);
         let light = Light::new_point_light(Point::inew(0, 0, 10), Col::new(1.0, 1.0, 1.0));
-        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, false);
+        let res = Light::lighting_without_obj(&m, &light, &position, &v_eye, &v_normal, 1.0);
         assert_eq!(res, Col::new(0.1, 0.1, 0.1));
     }
 
@@ -217,8 +549,7 @@ mod tests {
         let eye_v = Vector::inew(0, 0, -1);
         let normal_v = Vector::inew(0, 0, -1);
         let light = Light::new_point_light(Point::inew(0, 0, -10), Col::new(1.0, 1.0, 1.0));
-        let in_shadow = true;
-        let res = Light::lighting_without_obj(&m, &light, &position, &eye_v, &normal_v, in_shadow);
+        let res = Light::lighting_without_obj(&m, &light, &position, &eye_v, &normal_v, 0.0);
         assert_eq!(res, Col::new(0.1, 0.1, 0.1));
     }
 
@@ -239,7 +570,7 @@ mod tests {
             &Point::new(0.9, 0.0, 0.0),
             &eye_v,
             &normal_v,
-            false,
+            1.0,
         );
         let c2 = Light::lighting_without_obj(
             &material,
@@ -247,9 +578,251 @@ mod tests {
             &Point::new(1.1, 0.0, 0.0),
             &eye_v,
             &normal_v,
-            false,
+            1.0,
         );
         assert_eq!(c1, WHITE);
         assert_eq!(c2, BLACK);
     }
+
+    // area lights
+    #[test]
+    fn a_point_light_has_a_single_sample_at_its_position() {
+        let light = Light::new_point_light(Point::inew(1, 2, 3), WHITE);
+        let samples = light.samples_from(&Point::inew(1, 2, 0));
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].direction, Vector::inew(0, 0, 1));
+        assert_eq!(samples[0].distance, 3.);
+    }
+
+    #[test]
+    fn creating_an_area_light() {
+        let corner = Point::inew(0, 0, 0);
+        let light = AreaLight::new(
+            corner,
+            Vector::inew(2, 0, 0),
+            4,
+            Vector::inew(0, 0, 1),
+            2,
+            WHITE,
+            false,
+        );
+        assert_eq!(light.corner, corner);
+        assert_eq!(light.uvec, Vector::new(0.5, 0., 0.));
+        assert_eq!(light.usteps, 4);
+        assert_eq!(light.vvec, Vector::new(0., 0., 0.5));
+        assert_eq!(light.vsteps, 2);
+        assert_eq!(light.samples(), 8);
+        assert_eq!(light.position, Point::new(1., 0., 0.5));
+    }
+
+    #[test]
+    fn finding_a_single_point_on_an_area_light() {
+        let light = AreaLight::new(
+            Point::inew(0, 0, 0),
+            Vector::inew(2, 0, 0),
+            4,
+            Vector::inew(0, 0, 1),
+            2,
+            WHITE,
+            false,
+        );
+        let mut rng = Rng::new(0);
+        let table = [
+            (0, 0, Point::new(0.25, 0., 0.25)),
+            (1, 0, Point::new(0.75, 0., 0.25)),
+            (0, 1, Point::new(0.25, 0., 0.75)),
+            (2, 0, Point::new(1.25, 0., 0.25)),
+            (3, 1, Point::new(1.75, 0., 0.75)),
+        ];
+        for (u, v, expected) in table {
+            assert_eq!(light.point_on_light(u, v, &mut rng), expected);
+        }
+    }
+
+    #[test]
+    fn jittered_points_stay_inside_their_cell() {
+        let light = AreaLight::new(
+            Point::inew(0, 0, 0),
+            Vector::inew(2, 0, 0),
+            4,
+            Vector::inew(0, 0, 1),
+            2,
+            WHITE,
+            true,
+        );
+        let samples = light.sample_points(&Point::inew(0, 5, 0));
+        assert_eq!(samples.len(), 8);
+        for (i, p) in samples.iter().enumerate() {
+            let (u, v) = ((i % 4) as f64, (i / 4) as f64);
+            assert!((u * 0.5..(u + 1.) * 0.5).contains(&p.x), "{:?}", p);
+            assert!((v * 0.5..(v + 1.) * 0.5).contains(&p.z), "{:?}", p);
+        }
+        // the same point always gets the same samples
+        assert_eq!(samples, light.sample_points(&Point::inew(0, 5, 0)));
+    }
+
+    #[test]
+    fn lighting_uses_light_intensity_to_attenuate_color() {
+        let mut m = Material::new();
+        m.ambient = 0.1;
+        m.diffuse = 0.9;
+        m.specular = 0.;
+        let light = Light::new_point_light(Point::inew(0, 0, -10), WHITE);
+        let pt = Point::inew(0, 0, -1);
+        let eye_v = Vector::inew(0, 0, -1);
+        let normal_v = Vector::inew(0, 0, -1);
+        for (intensity, expected) in [(1.0, 1.0), (0.5, 0.55), (0.0, 0.1)] {
+            let res = Light::lighting_without_obj(&m, &light, &pt, &eye_v, &normal_v, intensity);
+            assert_eq!(res, Col::new(expected, expected, expected));
+        }
+    }
+
+    #[test]
+    fn lighting_samples_the_area_light() {
+        let light = Light::new_area_light(
+            Point::new(-0.5, -0.5, -5.),
+            Vector::inew(1, 0, 0),
+            2,
+            Vector::inew(0, 1, 0),
+            2,
+            WHITE,
+            false,
+        );
+        let mut m = Material::new();
+        m.ambient = 0.1;
+        m.diffuse = 0.9;
+        m.specular = 0.;
+        let eye = Point::inew(0, 0, -5);
+        for (pt, expected) in [
+            (Point::inew(0, 0, -1), 0.9965),
+            (Point::new(0., FRAC_1_SQRT_2, -FRAC_1_SQRT_2), 0.62318),
+        ] {
+            let eye_v = (eye - pt).normalize();
+            let normal_v = pt - Point::new_origin();
+            let res = Light::lighting_without_obj(&m, &light, &pt, &eye_v, &normal_v, 1.0);
+            assert_eq!(res, Col::new(expected, expected, expected));
+        }
+    }
+
+    // spot & directional lights
+    #[test]
+    fn spot_light_fades_out_between_inner_and_outer_cone() {
+        let light = Light::new_spot_light(
+            Point::inew(0, 10, 0),
+            Vector::inew(0, -2, 0),
+            PI / 8.,
+            PI / 4.,
+            WHITE,
+        );
+        // straight below the light
+        assert_eq!(light.falloff(&Point::inew(0, 0, 0)), 1.0);
+        // just outside of the 45deg outer cone
+        assert_eq!(light.falloff(&Point::inew(11, 0, 0)), 0.0);
+        assert_eq!(light.falloff(&Point::inew(0, 20, 0)), 0.0);
+        // somewhere between inner and outer cone
+        let between = light.falloff(&Point::new(6., 0., 0.));
+        assert!(0.0 < between && between < 1.0, "{between}");
+    }
+
+    #[test]
+    fn lighting_outside_of_the_spot_cone_is_only_ambient() {
+        let (m, _) = setup_mat_pos();
+        let eye_v = Vector::inew(0, 0, -1);
+        let normal_v = Vector::inew(0, 0, -1);
+        let light = Light::new_spot_light(
+            Point::inew(0, 0, -10),
+            Vector::inew(0, 0, 1),
+            0.1,
+            0.2,
+            WHITE,
+        );
+        let inside = Point::inew(0, 0, 0);
+        let outside = Point::inew(5, 0, 0);
+        let res = Light::lighting_without_obj(&m, &light, &inside, &eye_v, &normal_v, 1.0);
+        assert_eq!(res, Col::new(1.9, 1.9, 1.9));
+        let res = Light::lighting_without_obj(&m, &light, &outside, &eye_v, &normal_v, 1.0);
+        assert_eq!(res, Col::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn directional_light_has_no_distance() {
+        let light = Light::new_directional_light(Vector::inew(0, 0, 3), WHITE);
+        assert_eq!(light.position(), None);
+        for point in [Point::inew(0, 0, 0), Point::inew(100, -5, 20)] {
+            let samples = light.samples_from(&point);
+            assert_eq!(samples.len(), 1);
+            assert_eq!(samples[0].direction, Vector::inew(0, 0, -1));
+            assert_eq!(samples[0].distance, f64::INFINITY);
+        }
+    }
+
+    #[test]
+    fn lighting_with_a_directional_light() {
+        let (m, position) = setup_mat_pos();
+        let eye_v = Vector::inew(0, 0, -1);
+        let normal_v = Vector::inew(0, 0, -1);
+        // same as a point light far away at (0, 0, -10)
+        let light = Light::new_directional_light(Vector::inew(0, 0, 1), WHITE);
+        let res = Light::lighting_without_obj(&m, &light, &position, &eye_v, &normal_v, 1.0);
+        assert_eq!(res, Col::new(1.9, 1.9, 1.9));
+    }
+
+    // attenuation
+    #[test]
+    fn attenuation_factors() {
+        assert_eq!(Attenuation::None.factor(100.), 1.0);
+        assert_eq!(Attenuation::InverseSquare.factor(2.), 0.25);
+        let poly = Attenuation::Polynomial {
+            constant: 1.,
+            linear: 0.5,
+            quadratic: 0.25,
+        };
+        assert_eq!(poly.factor(0.), 1.0);
+        assert_eq!(poly.factor(2.), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn attenuation_never_divides_by_zero() {
+        let zero = Attenuation::Polynomial {
+            constant: 0.,
+            linear: 0.,
+            quadratic: 0.,
+        };
+        assert_eq!(zero.factor(3.), 1.0);
+        let quadratic = Attenuation::Polynomial {
+            constant: 0.,
+            linear: 0.,
+            quadratic: 1.,
+        };
+        assert_eq!(quadratic.factor(0.), 1.0);
+        assert_eq!(Attenuation::InverseSquare.factor(0.), 1.0);
+    }
+
+    #[test]
+    fn lighting_with_an_attenuated_light() {
+        let mut m = Material::new();
+        m.ambient = 0.1;
+        m.diffuse = 0.9;
+        m.specular = 0.;
+        let eye_v = Vector::inew(0, 0, -1);
+        let normal_v = Vector::inew(0, 0, -1);
+        let light = Light::new_point_light(Point::inew(0, 0, -10), Col::new(100., 100., 100.))
+            .with_attenuation(Attenuation::InverseSquare);
+        assert_eq!(light.attenuation(), Attenuation::InverseSquare);
+        // 100 * (0.1 + 0.9) / 10^2
+        let res =
+            Light::lighting_without_obj(&m, &light, &Point::inew(0, 0, 0), &eye_v, &normal_v, 1.0);
+        assert_eq!(res, Col::new(1., 1., 1.));
+        // 5 units further away only gets a 1/2.25 of that
+        let res =
+            Light::lighting_without_obj(&m, &light, &Point::inew(0, 0, 5), &eye_v, &normal_v, 1.0);
+        assert_eq!(res, Col::new(1. / 2.25, 1. / 2.25, 1. / 2.25));
+    }
+
+    #[test]
+    fn directional_lights_ignore_attenuation() {
+        let light = Light::new_directional_light(Vector::inew(0, -1, 0), WHITE)
+            .with_attenuation(Attenuation::InverseSquare);
+        assert_eq!(light.attenuation(), Attenuation::None);
+    }
 }
@@ -1,28 +1,63 @@
+use std::f64::consts::PI;
+
 use crate::{
+    cmp::EPSILON,
     mathstructs::{matrix::Matrix, point::Point},
+    noise::{perlin, Fractal},
     object::Object,
 };
 
-use super::color::Col;
+use super::{
+    color::Col,
+    uv::{UvImage, UvMapping},
+};
 
+/// the pattern tree. Instead of plain colors, patterns are made out of other patterns
+/// (that can have their own transform). A plain color is just a Single pattern.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Texture {
     Single(Col),
-    Stripe(Col, Col),
-    Gradient(Col, Col),
-    Ring(Col, Col),
-    Checker(Col, Col),
+    Stripe(Box<Pattern>, Box<Pattern>),
+    Gradient(Box<Pattern>, Box<Pattern>),
+    Ring(Box<Pattern>, Box<Pattern>),
+    Checker(Box<Pattern>, Box<Pattern>),
+    /// the average of both patterns
+    Blend(Box<Pattern>, Box<Pattern>),
+    /// moves the point (by up to scale) with noise before looking up the pattern
+    Perturb(Box<Pattern>, f64),
+    /// veins along x, bent by turbulence
+    Marble(Box<Pattern>, Box<Pattern>, Fractal),
+    /// rings around the y axis (like Ring), bent by turbulence
+    Wood(Box<Pattern>, Box<Pattern>, Fractal),
+    /// a goes to b where the noise is high
+    Clouds(Box<Pattern>, Box<Pattern>, Fractal),
+    /// an image wrapped around the object
+    Map(UvMapping, UvImage),
     TestPattern,
 }
 
 impl Texture {
+    /// color at the point (in the space of the pattern that holds this texture)
     pub fn at(&self, point: &Point) -> Col {
         match self {
-            Texture::Stripe(a, b) => stripe_at(point, a, b),
+            Texture::Stripe(a, b) => stripe_at(point, a, b).at(point),
             Texture::Single(a) => *a,
-            Texture::Gradient(a, b) => gradient_at(point, a, b),
-            Texture::Ring(a, b) => ring_at(point, a, b),
-            Texture::Checker(a, b) => checker_at(point, a, b),
+            Texture::Gradient(a, b) => gradient_at(point, a.at(point), b.at(point)),
+            Texture::Ring(a, b) => ring_at(point, a, b).at(point),
+            Texture::Checker(a, b) => checker_at(point, a, b).at(point),
+            Texture::Blend(a, b) => (a.at(point) + b.at(point)) * 0.5,
+            Texture::Perturb(pattern, scale) => pattern.at(&perturb(point, *scale)),
+            Texture::Marble(a, b, fractal) => {
+                mix(a.at(point), b.at(point), marble_at(point, fractal))
+            }
+            Texture::Wood(a, b, fractal) => mix(a.at(point), b.at(point), wood_at(point, fractal)),
+            Texture::Clouds(a, b, fractal) => {
+                mix(a.at(point), b.at(point), clouds_at(point, fractal))
+            }
+            Texture::Map(mapping, image) => {
+                let (u, v) = mapping.uv(point);
+                image.at(u, v)
+            }
             Texture::TestPattern => test_at(point),
         }
     }
@@ -31,99 +66,188 @@ impl Texture {
 #[derive(Debug, Clone)]
 pub struct Pattern {
     pub texture: Texture,
-    pub transform: Option<Matrix>,
+    /// set it with set_pattern_transform() or with_pattern_transform() so the cached inverse gets updated
+    transform: Option<Matrix>,
+    /// cached inverse of the transform
+    inverse: Option<Matrix>,
 }
 
 impl Pattern {
+    fn new(texture: Texture) -> Self {
+        Self {
+            texture,
+            transform: None,
+            inverse: None,
+        }
+    }
+
     /// sets pattern transform. (default is None)
     pub fn with_pattern_transform(mut self, transform: Matrix) -> Self {
-        self.transform = Some(transform);
+        self.set_pattern_transform(transform);
         self
     }
 
+    /// sets pattern transform. (default is None)
+    pub fn set_pattern_transform(&mut self, transform: Matrix) {
+        self.transform = Some(transform);
+        self.inverse = Some(transform.inverse());
+    }
+
+    pub fn transform(&self) -> Option<&Matrix> {
+        self.transform.as_ref()
+    }
+
     pub fn new_single(a: Col) -> Self {
-        Self {
-            texture: Texture::Single(a),
-            transform: None,
-        }
+        Self::new(Texture::Single(a))
     }
 
-    pub fn new_stripe(a: Col, b: Col) -> Self {
-        Self {
-            texture: Texture::Stripe(a, b),
-            transform: None,
-        }
+    // the patterns below take colors or other patterns
+
+    pub fn new_stripe(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(Texture::Stripe(Box::new(a.into()), Box::new(b.into())))
     }
 
-    pub fn new_gradient(a: Col, b: Col) -> Self {
-        Self {
-            texture: Texture::Gradient(a, b),
-            transform: None,
-        }
+    pub fn new_gradient(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(Texture::Gradient(Box::new(a.into()), Box::new(b.into())))
     }
 
-    pub fn new_ring(a: Col, b: Col) -> Self {
-        Self {
-            texture: Texture::Ring(a, b),
-            transform: None,
-        }
+    pub fn new_ring(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(Texture::Ring(Box::new(a.into()), Box::new(b.into())))
     }
 
-    pub fn new_checkers(a: Col, b: Col) -> Self {
-        Self {
-            texture: Texture::Checker(a, b),
-            transform: None,
-        }
+    pub fn new_checkers(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(Texture::Checker(Box::new(a.into()), Box::new(b.into())))
+    }
+
+    pub fn new_blend(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(Texture::Blend(Box::new(a.into()), Box::new(b.into())))
+    }
+
+    /// scale: how far (at most) the point gets moved. Around 0.1 - 0.5 looks good for most patterns.
+    pub fn new_perturb(pattern: impl Into<Pattern>, scale: f64) -> Self {
+        Self::new(Texture::Perturb(Box::new(pattern.into()), scale))
+    }
+
+    // noise textures. The fractal picks the seed, the octaves and the frequency of the noise.
+
+    pub fn new_marble(a: impl Into<Pattern>, b: impl Into<Pattern>, fractal: Fractal) -> Self {
+        Self::new(Texture::Marble(
+            Box::new(a.into()),
+            Box::new(b.into()),
+            fractal,
+        ))
+    }
+
+    pub fn new_wood(a: impl Into<Pattern>, b: impl Into<Pattern>, fractal: Fractal) -> Self {
+        Self::new(Texture::Wood(
+            Box::new(a.into()),
+            Box::new(b.into()),
+            fractal,
+        ))
+    }
+
+    pub fn new_clouds(a: impl Into<Pattern>, b: impl Into<Pattern>, fractal: Fractal) -> Self {
+        Self::new(Texture::Clouds(
+            Box::new(a.into()),
+            Box::new(b.into()),
+            fractal,
+        ))
+    }
+
+    /// the mapping should fit the shape: spherical for spheres, cube for cubes...
+    pub fn new_map(mapping: UvMapping, image: UvImage) -> Self {
+        Self::new(Texture::Map(mapping, image))
     }
 
     pub fn new_test_pattern() -> Self {
-        Self {
-            texture: Texture::TestPattern,
-            transform: None,
-        }
+        Self::new(Texture::TestPattern)
     }
 
     pub fn at_with_obj(&self, object: &Object, world_point: &Point) -> Col {
-        let object_point = object.transformation.inverse() * *world_point;
-        match self.transform {
-            Some(t) => self.texture.at(&(t.inverse() * object_point)),
-            None => self.texture.at(&object_point),
+        self.at(&object.world_to_obj(*world_point))
+    }
+
+    /// color at a point in the space of whatever holds the pattern (the object or a parent pattern)
+    pub fn at(&self, point: &Point) -> Col {
+        match self.inverse {
+            Some(inverse) => self.texture.at(&(inverse * *point)),
+            None => self.texture.at(point),
         }
     }
 }
 
+impl From<Col> for Pattern {
+    fn from(col: Col) -> Self {
+        Pattern::new_single(col)
+    }
+}
+
 impl PartialEq for Pattern {
     fn eq(&self, other: &Self) -> bool {
         self.texture == other.texture && self.transform == other.transform
     }
 }
 
-fn stripe_at<'a>(point: &Point, a: &'a Col, b: &'a Col) -> Col {
+fn stripe_at<'a>(point: &Point, a: &'a Pattern, b: &'a Pattern) -> &'a Pattern {
     if point.x.floor() % 2.0 == 0.0 {
-        return *a;
+        return a;
     }
-    *b
+    b
 }
 
-fn gradient_at<'a>(point: &Point, a: &'a Col, b: &'a Col) -> Col {
-    *a + (*b - *a) * (point.x - point.x.floor())
+fn gradient_at(point: &Point, a: Col, b: Col) -> Col {
+    a + (b - a) * (point.x - point.x.floor())
 }
 
-fn ring_at<'a>(point: &Point, a: &'a Col, b: &'a Col) -> Col {
+fn ring_at<'a>(point: &Point, a: &'a Pattern, b: &'a Pattern) -> &'a Pattern {
     if ((point.x * point.x + point.z * point.z).sqrt()).floor() % 2.0 == 0.0 {
-        return *a;
+        return a;
     }
-    *b
+    b
 }
 
-fn checker_at<'a>(point: &Point, a: &'a Col, b: &'a Col) -> Col {
-    let sum = f64::floor(point.x).abs() as u32
-        + f64::abs(point.y).abs() as u32
-        + f64::abs(point.z).abs() as u32;
-    if sum % 2 == 0 {
-        return *a;
+fn checker_at<'a>(point: &Point, a: &'a Pattern, b: &'a Pattern) -> &'a Pattern {
+    // flooring (instead of truncating) keeps the cells the same size on both sides of 0.
+    // nudged by EPSILON, so points right on a border (like hits on a plane at y = 0 that come
+    // out as -0.0000000000000002) dont flicker between both cells
+    let cell = |c: f64| (c + EPSILON).floor();
+    let sum = cell(point.x) + cell(point.y) + cell(point.z);
+    if sum.rem_euclid(2.0) == 0.0 {
+        return a;
     }
-    *b
+    b
+}
+
+fn mix(a: Col, b: Col, t: f64) -> Col {
+    a + (b - a) * t
+}
+
+/// 0..1, a sine wave along x (one vein every 2 units) that the turbulence pushes around
+fn marble_at(point: &Point, fractal: &Fractal) -> f64 {
+    let turbulence = fractal.turbulence(point);
+    0.5 + 0.5 * (PI * (point.x + 4. * turbulence)).sin()
+}
+
+/// 0..1, goes from a to b inside every ring and then starts over
+fn wood_at(point: &Point, fractal: &Fractal) -> f64 {
+    let distance = (point.x * point.x + point.z * point.z).sqrt();
+    let rings = distance + 0.5 * fractal.turbulence(point);
+    rings - rings.floor()
+}
+
+/// 0..1, soft blobs
+fn clouds_at(point: &Point, fractal: &Fractal) -> f64 {
+    (0.5 + fractal.fbm(point)).clamp(0., 1.)
+}
+
+/// every axis gets its own noise, by looking it up at points far away from each other
+fn perturb(point: &Point, scale: f64) -> Point {
+    let (x, y, z) = (point.x, point.y, point.z);
+    Point::new(
+        x + perlin(x, y, z) * scale,
+        y + perlin(x, y, z + 31.) * scale,
+        z + perlin(x, y, z + 67.) * scale,
+    )
 }
 
 fn test_at(point: &Point) -> Col {
@@ -133,8 +257,10 @@ fn test_at(point: &Point) -> Col {
 #[cfg(test)]
 mod tests {
     use crate::{
-        mathstructs::matrix::Matrix,
-        object::sphere::Sphere,
+        mathstructs::{matrix::Matrix, vector::Vector},
+        object::{plane::Plane, sphere::Sphere},
+        random::Rng,
+        ray::Ray,
         visual::color::{BLACK, WHITE},
     };
 
@@ -180,7 +306,7 @@ mod tests {
     fn stripes_with_a_pattern_transformation() {
         let object = Sphere::new();
         let mut pattern = Pattern::new_stripe(WHITE, BLACK);
-        pattern.transform = Some(Matrix::scaling_new(2.0, 2.0, 2.0));
+        pattern.set_pattern_transform(Matrix::scaling_new(2.0, 2.0, 2.0));
         let res = pattern.at_with_obj(&object, &Point::new(1.5, 0.0, 0.0));
         assert_eq!(res, WHITE);
     }
@@ -189,7 +315,7 @@ mod tests {
     fn stripes_with_obj_and_pattern_transformation() {
         let object = Sphere::new().with_transform(Matrix::scaling_new(2.0, 2.0, 2.0));
         let mut pattern = Pattern::new_stripe(WHITE, BLACK);
-        pattern.transform = Some(Matrix::translation_new(0.5, 0.0, 0.0));
+        pattern.set_pattern_transform(Matrix::translation_new(0.5, 0.0, 0.0));
         let res = pattern.at_with_obj(&object, &Point::new(2.5, 0.0, 0.0));
         assert_eq!(res, WHITE);
     }
@@ -236,6 +362,23 @@ mod tests {
         assert_eq!(pattern.texture.at(&Point::new(0.0, 0.0, 0.0)), WHITE);
         assert_eq!(pattern.texture.at(&Point::new(0.0, 0.0, 0.99)), WHITE);
         assert_eq!(pattern.texture.at(&Point::new(0.0, 0.0, 1.01)), BLACK);
+        // and keeps alternating for negative values
+        assert_eq!(pattern.texture.at(&Point::new(0.0, 0.0, -0.5)), BLACK);
+        assert_eq!(pattern.texture.at(&Point::new(-0.5, 0.0, -0.5)), WHITE);
+        assert_eq!(pattern.texture.at(&Point::new(0.0, -1.5, 0.0)), WHITE);
+    }
+
+    #[test]
+    fn checkers_on_a_plane_at_y_0_dont_flicker() {
+        let plane = Plane::new();
+        let pattern = Pattern::new_checkers(WHITE, BLACK);
+        // rounding puts this hit a tiny bit below the plane
+        let ray = Ray::new(Point::new(0.5, 1.7, 0.5), Vector::new(0., -0.7, 0.));
+        let point = ray.position(plane.intersect(&ray)[0].t);
+        assert!(point.y < 0.0);
+        assert_eq!(pattern.at_with_obj(&plane, &point), WHITE);
+        assert_eq!(pattern.at(&Point::new(1.5, -1e-16, 0.5)), BLACK);
+        assert_eq!(pattern.at(&Point::new(-0.5, -1e-16, 0.5)), BLACK);
     }
 
     #[test]
@@ -257,4 +400,114 @@ mod tests {
             Col::new(0.75, 0.5, 0.25)
         );
     }
+
+    // nested patterns
+    #[test]
+    fn patterns_can_be_made_out_of_patterns() {
+        let red = Col::new(1., 0., 0.);
+        let stripes = Pattern::new_stripe(WHITE, BLACK);
+        let rotated = Pattern::new_stripe(red, BLACK)
+            .with_pattern_transform(Matrix::rotation_y_new(std::f64::consts::PI / 2.));
+        let pattern = Pattern::new_checkers(stripes, rotated);
+        // even cells: stripes along x
+        assert_eq!(pattern.at(&Point::new(0.5, 0.5, 0.5)), WHITE);
+        assert_eq!(pattern.at(&Point::new(1.5, 0.5, 1.5)), BLACK);
+        // odd cells: the rotated stripes, so they change along z instead
+        assert_eq!(pattern.at(&Point::new(0.5, 0.5, 1.5)), red);
+        assert_eq!(pattern.at(&Point::new(1.5, 0.5, 0.5)), BLACK);
+        assert_eq!(pattern.at(&Point::new(1.5, 0.5, 2.5)), BLACK);
+        assert_eq!(pattern.at(&Point::new(2.5, 0.5, 1.5)), red);
+    }
+
+    #[test]
+    fn nested_patterns_apply_all_transforms() {
+        let inner =
+            Pattern::new_test_pattern().with_pattern_transform(Matrix::translation_new(1., 0., 0.));
+        let outer = Pattern::new_stripe(inner, BLACK)
+            .with_pattern_transform(Matrix::scaling_new(2., 2., 2.));
+        // outer: (1, 1, 1) -> (0.5, 0.5, 0.5), which is in the inner stripe. Inner -> (-0.5, 0.5, 0.5)
+        assert_eq!(outer.at(&Point::new(1., 1., 1.)), Col::new(-0.5, 0.5, 0.5));
+        assert_eq!(outer.at(&Point::new(3., 1., 1.)), BLACK);
+    }
+
+    #[test]
+    fn blending_averages_two_patterns() {
+        let pattern = Pattern::new_blend(
+            Pattern::new_stripe(WHITE, BLACK),
+            Pattern::new_stripe(WHITE, BLACK)
+                .with_pattern_transform(Matrix::rotation_y_new(std::f64::consts::PI / 2.)),
+        );
+        let grey = Col::new(0.5, 0.5, 0.5);
+        assert_eq!(pattern.at(&Point::new(0.5, 0., -0.5)), WHITE);
+        assert_eq!(pattern.at(&Point::new(0.5, 0., 0.5)), grey);
+        assert_eq!(pattern.at(&Point::new(1.5, 0., -0.5)), grey);
+        assert_eq!(pattern.at(&Point::new(1.5, 0., 0.5)), BLACK);
+    }
+
+    #[test]
+    fn perturbing_moves_the_lookup_point_a_bit() {
+        let pattern = Pattern::new_perturb(Pattern::new_test_pattern(), 0.2);
+        let point = Point::new(0.3, 0.6, 0.2);
+        let moved = pattern.at(&point);
+        assert_ne!(moved, Col::new(0.3, 0.6, 0.2));
+        assert!(moved.max_difference(&Col::new(0.3, 0.6, 0.2)) <= 0.2);
+        // the same point always gets moved the same way
+        assert_eq!(pattern.at(&point), moved);
+        // no noise on whole numbers
+        assert_eq!(pattern.at(&Point::inew(1, 2, 3)), Col::new(1., 2., 3.));
+    }
+
+    // noise textures
+    #[test]
+    fn noise_textures_stay_between_their_colors() {
+        let textures = [
+            Pattern::new_marble(WHITE, BLACK, Fractal::default()),
+            Pattern::new_wood(WHITE, BLACK, Fractal::new(1, 2, 3.)),
+            Pattern::new_clouds(WHITE, BLACK, Fractal::new(2, 6, 0.5)),
+        ];
+        let mut rng = Rng::new(5);
+        for pattern in textures {
+            let mut greys = vec![];
+            for _ in 0..200 {
+                let point = Point::new(
+                    rng.next_f64() * 8. - 4.,
+                    rng.next_f64() * 8. - 4.,
+                    rng.next_f64() * 8. - 4.,
+                );
+                let col = pattern.at(&point);
+                assert!((0.0..=1.0).contains(&col.r), "{pattern:?} {col:?}");
+                assert!(col.r == col.g && col.g == col.b);
+                // deterministic
+                assert_eq!(pattern.at(&point), col);
+                greys.push(col.r);
+            }
+            // uses the whole range, not just one color
+            assert!(greys.iter().any(|grey| *grey < 0.3), "{pattern:?}");
+            assert!(greys.iter().any(|grey| *grey > 0.7), "{pattern:?}");
+        }
+    }
+
+    #[test]
+    fn noise_textures_follow_their_base_pattern() {
+        // without octaves there is no noise, so they are just the plain patterns
+        let flat = Fractal::new(0, 0, 1.);
+        let marble = Pattern::new_marble(WHITE, BLACK, flat.clone());
+        assert_eq!(marble.at(&Point::new(0.5, 3., 2.)), BLACK);
+        assert_eq!(marble.at(&Point::new(1.5, -1., 0.)), WHITE);
+        let wood = Pattern::new_wood(WHITE, BLACK, flat.clone());
+        assert_eq!(wood.at(&Point::new(0., 7., 2.)), WHITE);
+        assert_eq!(wood.at(&Point::new(0.6, 0., 0.8)), WHITE);
+        assert_eq!(wood.at(&Point::new(0., 0., 1.5)), Col::new(0.5, 0.5, 0.5));
+        let clouds = Pattern::new_clouds(WHITE, BLACK, flat);
+        assert_eq!(
+            clouds.at(&Point::new(0.3, 0.2, 0.1)),
+            Col::new(0.5, 0.5, 0.5)
+        );
+        // other seeds give other textures
+        let point = Point::new(0.3, 0.2, 0.1);
+        assert_ne!(
+            Pattern::new_clouds(WHITE, BLACK, Fractal::new(1, 4, 1.)).at(&point),
+            Pattern::new_clouds(WHITE, BLACK, Fractal::new(2, 4, 1.)).at(&point)
+        );
+    }
 }
@@ -0,0 +1,799 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::random::Rng;
+
+use super::{
+    camera::Camera,
+    canvas::{Accumulator, Canvas},
+    color::{Col, BLACK},
+    world::World,
+};
+
+/// where inside a pixel the rays get shot through, when using more than one sample per pixel.
+/// - stratified and jittered split the pixel into a grid of (about) samples cells
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleMode {
+    /// the center of each cell
+    Stratified,
+    /// a random point inside each cell. Trades the regular patterns for a bit of noise.
+    Jittered,
+    /// shoots rays through the pixel corners and only splits the pixel into 4 (again and again,
+    /// up to max_depth times) where the corners differ by more than the threshold.
+    /// - ignores the samples setting, flat areas only cost about 1 ray per pixel
+    Adaptive { threshold: f64, max_depth: u8 },
+}
+
+/// how a scene gets rendered. Stored with the scene, so every front end renders it the same.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSettings {
+    /// how often a ray can get reflected or refracted in a row
+    pub max_depth: u8,
+    /// rays that would contribute less than this to the pixel (after losing most of it to
+    /// multiple reflections or refractions) dont get followed any further. 0 turns that off.
+    pub min_weight: f64,
+    /// rays per pixel that get averaged (anti-aliasing). 1 just uses the pixel's center.
+    pub samples: usize,
+    pub sample_mode: SampleMode,
+    /// the image gets rendered in square tiles of this size (in pixels)
+    pub tile_size: usize,
+    /// progressive rendering: how often the image gets rendered (with samples rays per pixel
+    /// each time) and averaged. See Renderer::render_progressive.
+    pub passes: usize,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            max_depth: 5,
+            // less than that does not even change a 0-255 color value
+            min_weight: 0.001,
+            samples: 1,
+            sample_mode: SampleMode::Jittered,
+            tile_size: 16,
+            passes: 1,
+        }
+    }
+}
+
+/// gets told how far a render got. Tiles might finish on different threads, so it has to be Sync.
+pub trait Progress: Sync {
+    /// called once before rendering, with the number of tiles that will get rendered
+    fn start(&self, _tiles: usize) {}
+    /// called after every finished tile
+    fn tile_done(&self) {}
+    /// called once after the last tile
+    fn finish(&self) {}
+    /// gets checked before every tile. Once it says true the remaining tiles get skipped (and
+    /// stay black). Tiles that already started still get finished.
+    fn should_stop(&self) -> bool {
+        false
+    }
+}
+
+/// for when nobody cares about the progress
+pub struct NoProgress;
+
+impl Progress for NoProgress {}
+
+/// counts the finished tiles and lets whoever holds onto it (another thread, a ctrl-c handler...)
+/// stop the render early. The render then returns what it got so far.
+#[derive(Debug, Default)]
+pub struct RenderToken {
+    total: AtomicUsize,
+    done: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl RenderToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn tiles_done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// 0 till the render started
+    pub fn tiles_total(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+}
+
+impl Progress for RenderToken {
+    fn start(&self, tiles: usize) {
+        self.total.store(tiles, Ordering::Relaxed);
+        self.done.store(0, Ordering::Relaxed);
+    }
+
+    fn tile_done(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.is_cancelled()
+    }
+}
+
+/// a rectangle of pixels of the image. x0, y0 is its top left pixel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tile {
+    pub x0: usize,
+    pub y0: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// renders the world as seen by the camera. All front ends go trough this, so the same scene
+/// ends up with the same pixels everywhere.
+/// - with the `rayon` feature the tiles get rendered in parallel
+pub struct Renderer<'a> {
+    pub camera: &'a Camera,
+    pub world: &'a World,
+    pub settings: RenderSettings,
+    /// which progressive pass we are rendering. Every pass shoots its rays through other points
+    /// of the pixels.
+    pass: usize,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(camera: &'a Camera, world: &'a World, settings: RenderSettings) -> Self {
+        assert!(settings.samples > 0, "need at least 1 sample per pixel");
+        assert!(
+            settings.tile_size > 0,
+            "tiles need to be at least 1 pixel big"
+        );
+        Self {
+            camera,
+            world,
+            settings,
+            pass: 0,
+        }
+    }
+
+    /// the same renderer, but for another progressive pass. Pass 0 is the normal render.
+    pub fn for_pass(&self, pass: usize) -> Self {
+        Self { pass, ..*self }
+    }
+
+    /// color of the ray through the point (dx, dy) inside the pixel
+    fn sample(&self, px: usize, py: usize, dx: f64, dy: f64) -> Col {
+        let ray = self.camera.ray_for_subpixel(px, py, dx, dy);
+        self.world.color_with_settings(&ray, &self.settings)
+    }
+
+    /// the points inside the pixel we shoot our rays through (see Camera::ray_for_subpixel).
+    /// - the jitter is seeded by the pixel (and pass), so rendering the same pixel twice gives the
+    ///   same result
+    /// - adaptive sampling decides on its points while rendering, so we just use the grid here
+    /// - passes after the first always get jittered, else they would all be the same
+    /// - if the samples do not fill a grid, the ones left over get spread along the diagonal of the
+    ///   whole pixel. So no part of the pixel gets left out and the samples stay centered.
+    pub fn subpixel_offsets(&self, px: usize, py: usize) -> Vec<(f64, f64)> {
+        let samples = self.settings.samples;
+        let jittered = self.pass > 0 || self.settings.sample_mode == SampleMode::Jittered;
+        if samples <= 1 && self.pass == 0 {
+            return vec![(0.5, 0.5)];
+        }
+        let cols = (samples as f64).sqrt().ceil() as usize;
+        let rows = samples / cols;
+        let left_over = samples - cols * rows;
+        let mut rng = match self.pass {
+            0 => Rng::from_f64s(&[px as f64, py as f64]),
+            pass => Rng::from_f64s(&[px as f64, py as f64, pass as f64]),
+        };
+        let mut in_cell = |col: usize, row: usize, cols: usize, rows: usize| {
+            let (jx, jy) = match jittered {
+                true => (rng.next_f64(), rng.next_f64()),
+                false => (0.5, 0.5),
+            };
+            (
+                (col as f64 + jx) / cols as f64,
+                (row as f64 + jy) / rows as f64,
+            )
+        };
+        let mut offsets: Vec<(f64, f64)> = (0..cols * rows)
+            .map(|i| in_cell(i % cols, i / cols, cols, rows))
+            .collect();
+        offsets.extend((0..left_over).map(|i| in_cell(i, i, left_over, left_over)));
+        offsets
+    }
+
+    /// the averaged color of all the samples of a pixel
+    pub fn pixel_color(&self, px: usize, py: usize) -> Col {
+        if let (SampleMode::Adaptive { .. }, 0) = (self.settings.sample_mode, self.pass) {
+            let corner = |dx, dy| self.sample(px, py, dx, dy);
+            let corners = [
+                corner(0., 0.),
+                corner(1., 0.),
+                corner(0., 1.),
+                corner(1., 1.),
+            ];
+            return self.refine_adaptive(px, py, (0., 0.), 1., corners, 0);
+        }
+        let offsets = self.subpixel_offsets(px, py);
+        let sum = offsets
+            .iter()
+            .fold(BLACK, |sum, (dx, dy)| sum + self.sample(px, py, *dx, *dy));
+        sum * (1.0 / offsets.len() as f64)
+    }
+
+    /// the colors of a whole row of pixels
+    pub fn row_colors(&self, py: usize) -> Vec<Col> {
+        self.row_segment(py, 0, self.camera.width)
+    }
+
+    /// the colors of the pixels x0 till x0 + width in the row py.
+    /// - adaptive sampling shares the rays through the corners with the neighbouring pixels here
+    fn row_segment(&self, py: usize, x0: usize, width: usize) -> Vec<Col> {
+        let (SampleMode::Adaptive { .. }, 0) = (self.settings.sample_mode, self.pass) else {
+            return (x0..x0 + width)
+                .map(|px| self.pixel_color(px, py))
+                .collect();
+        };
+        // the corners of pixel x are the top-left corner of pixel x and of pixel x+1
+        let corners = |dy| -> Vec<Col> {
+            (x0..=x0 + width)
+                .map(|px| self.sample(px, py, 0., dy))
+                .collect()
+        };
+        let (top, bottom) = (corners(0.), corners(1.));
+        (0..width)
+            .map(|i| {
+                let corners = [top[i], top[i + 1], bottom[i], bottom[i + 1]];
+                self.refine_adaptive(x0 + i, py, (0., 0.), 1., corners, 0)
+            })
+            .collect()
+    }
+
+    /// color of the square at (x0, y0) with the given size inside the pixel.
+    /// - corners are [top-left, top-right, bottom-left, bottom-right]
+    /// - if they differ too much we split the square into 4 and sample those
+    fn refine_adaptive(
+        &self,
+        px: usize,
+        py: usize,
+        (x0, y0): (f64, f64),
+        size: f64,
+        corners: [Col; 4],
+        depth: u8,
+    ) -> Col {
+        let SampleMode::Adaptive {
+            threshold,
+            max_depth,
+        } = self.settings.sample_mode
+        else {
+            unreachable!("only used for adaptive sampling")
+        };
+        let avg = (corners[0] + corners[1] + corners[2] + corners[3]) * 0.25;
+        if depth >= max_depth || corners.iter().all(|c| c.max_difference(&avg) <= threshold) {
+            return avg;
+        }
+        let half = size / 2.;
+        let sample = |dx, dy| self.sample(px, py, x0 + dx, y0 + dy);
+        let [tl, tr, bl, br] = corners;
+        let top = sample(half, 0.);
+        let left = sample(0., half);
+        let center = sample(half, half);
+        let right = sample(size, half);
+        let bottom = sample(half, size);
+        let quarters = [
+            ((x0, y0), [tl, top, left, center]),
+            ((x0 + half, y0), [top, tr, center, right]),
+            ((x0, y0 + half), [left, center, bl, bottom]),
+            ((x0 + half, y0 + half), [center, right, bottom, br]),
+        ];
+        quarters.iter().fold(BLACK, |sum, (origin, corners)| {
+            sum + self.refine_adaptive(px, py, *origin, half, *corners, depth + 1)
+        }) * 0.25
+    }
+
+    /// all tiles of the image, row by row. Tiles at the right and bottom edge might be smaller
+    /// than the tile_size.
+    pub fn tiles(&self) -> Vec<Tile> {
+        let (width, height) = (self.camera.width, self.camera.height);
+        let size = self.settings.tile_size;
+        (0..height)
+            .step_by(size)
+            .flat_map(|y0| {
+                (0..width).step_by(size).map(move |x0| Tile {
+                    x0,
+                    y0,
+                    width: size.min(width - x0),
+                    height: size.min(height - y0),
+                })
+            })
+            .collect()
+    }
+
+    /// renders only the pixels of the rectangle starting at (x0, y0) into a canvas of its size.
+    /// - whatever reaches outside of the image gets cut off
+    /// - the pixels are the same as in a full render, so the blocks can get rendered anywhere
+    ///   (other threads, workers...) and get stitched together with Canvas::paste
+    pub fn render_region(&self, x0: usize, y0: usize, width: usize, height: usize) -> Canvas {
+        let width = width.min(self.camera.width.saturating_sub(x0));
+        let height = height.min(self.camera.height.saturating_sub(y0));
+        let mut block = Canvas::new(width, height);
+        for (row, py) in block.arr.iter_mut().zip(y0..) {
+            *row = self.row_segment(py, x0, width);
+        }
+        block
+    }
+
+    pub fn render_tile(&self, tile: &Tile) -> Canvas {
+        self.render_region(tile.x0, tile.y0, tile.width, tile.height)
+    }
+
+    pub fn render(&self) -> Canvas {
+        self.render_with_progress(&NoProgress)
+    }
+
+    pub fn render_with_progress(&self, progress: &dyn Progress) -> Canvas {
+        let tiles = self.tiles();
+        progress.start(tiles.len());
+        let render_tile = |tile: &Tile| {
+            if progress.should_stop() {
+                return None;
+            }
+            let block = self.render_tile(tile);
+            progress.tile_done();
+            Some((tile.x0, tile.y0, block))
+        };
+        #[cfg(feature = "rayon")]
+        let rendered: Vec<_> = {
+            use rayon::prelude::*;
+            tiles.par_iter().map(render_tile).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let rendered: Vec<_> = tiles.iter().map(render_tile).collect();
+
+        let canvas = Canvas::stitch(
+            self.camera.width,
+            self.camera.height,
+            rendered.into_iter().flatten(),
+        );
+        progress.finish();
+        canvas
+    }
+
+    /// renders the image settings.passes times and averages the passes. Each pass gets a quick
+    /// (few samples) but complete image, so there is something to look at early on.
+    /// - on_pass gets the average of all passes so far after every pass
+    /// - when stopped, the unfinished pass gets thrown away (unless it is the first one)
+    /// - adaptive sampling only gets used in the first pass, the others just jitter
+    pub fn render_progressive(
+        &self,
+        progress: &dyn Progress,
+        mut on_pass: impl FnMut(usize, &Canvas),
+    ) -> Canvas {
+        let mut acc = Accumulator::new(self.camera.width, self.camera.height);
+        for pass in 0..self.settings.passes.max(1) {
+            let image = self.for_pass(pass).render_with_progress(progress);
+            if progress.should_stop() && pass > 0 {
+                break;
+            }
+            acc.add(&image);
+            on_pass(acc.passes(), &acc.image());
+            if progress.should_stop() {
+                break;
+            }
+        }
+        acc.image()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        cmp::ApproxEq,
+        mathstructs::{matrix::Matrix, point::Point, vector::Vector},
+    };
+
+    use super::*;
+
+    fn test_camera() -> Camera {
+        Camera::new(11, 11, PI / 2.0).with_transform(Matrix::view_transform_new(
+            Point::inew(0, 0, -5),
+            Point::inew(0, 0, 0),
+            Vector::inew(0, 1, 0),
+        ))
+    }
+
+    fn with_sampling(samples: usize, sample_mode: SampleMode) -> RenderSettings {
+        RenderSettings {
+            samples,
+            sample_mode,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rendering_a_world() {
+        let (w, c) = (World::default(), test_camera());
+        let image = Renderer::new(&c, &w, RenderSettings::default()).render();
+        assert_eq!(image[5][5], Col::new(0.38066, 0.47583, 0.2855));
+    }
+
+    // supersampling
+    #[test]
+    fn a_single_sample_goes_through_the_pixel_center() {
+        let (w, c) = (World::default(), test_camera());
+        let r = Renderer::new(&c, &w, with_sampling(1, SampleMode::Jittered));
+        assert_eq!(r.subpixel_offsets(3, 7), vec![(0.5, 0.5)]);
+    }
+
+    #[test]
+    fn stratified_samples_are_the_centers_of_a_grid() {
+        let (w, c) = (World::default(), test_camera());
+        let r = Renderer::new(&c, &w, with_sampling(4, SampleMode::Stratified));
+        assert_eq!(
+            r.subpixel_offsets(0, 0),
+            vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+        );
+        // not a square number of samples
+        let r = Renderer::new(&c, &w, with_sampling(2, SampleMode::Stratified));
+        assert_eq!(r.subpixel_offsets(0, 0), vec![(0.25, 0.5), (0.75, 0.5)]);
+        // a grid of 2 x 1 and one left over
+        let r = Renderer::new(&c, &w, with_sampling(3, SampleMode::Stratified));
+        assert_eq!(
+            r.subpixel_offsets(0, 0),
+            vec![(0.25, 0.5), (0.75, 0.5), (0.5, 0.5)]
+        );
+    }
+
+    #[test]
+    fn samples_that_do_not_fill_a_grid_stay_centered() {
+        let (w, c) = (World::default(), test_camera());
+        let mean = |offsets: &[(f64, f64)]| {
+            let (x, y) = offsets
+                .iter()
+                .fold((0., 0.), |(x, y), (dx, dy)| (x + dx, y + dy));
+            (x / offsets.len() as f64, y / offsets.len() as f64)
+        };
+        for samples in [3, 5, 7, 8] {
+            let r = Renderer::new(&c, &w, with_sampling(samples, SampleMode::Stratified));
+            let offsets = r.subpixel_offsets(0, 0);
+            assert_eq!(offsets.len(), samples);
+            let (x, y) = mean(&offsets);
+            assert!(x.apx_eq(&0.5) && y.apx_eq(&0.5), "{samples}: ({x}, {y})");
+
+            // jittered samples are centered on average
+            let r = Renderer::new(&c, &w, with_sampling(samples, SampleMode::Jittered));
+            let all: Vec<(f64, f64)> = (0..400).flat_map(|px| r.subpixel_offsets(px, 3)).collect();
+            assert_eq!(all.len(), 400 * samples);
+            let (x, y) = mean(&all);
+            assert!(
+                (x - 0.5).abs() < 0.02 && (y - 0.5).abs() < 0.02,
+                "{samples}: ({x}, {y})"
+            );
+            // and every quarter of the pixel gets some
+            for (qx, qy) in [(0., 0.), (0.5, 0.), (0., 0.5), (0.5, 0.5)] {
+                assert!(all
+                    .iter()
+                    .any(|(dx, dy)| (qx..qx + 0.5).contains(dx) && (qy..qy + 0.5).contains(dy)));
+            }
+        }
+    }
+
+    #[test]
+    fn jittered_samples_stay_inside_their_cell() {
+        let (w, c) = (World::default(), test_camera());
+        let r = Renderer::new(&c, &w, with_sampling(16, SampleMode::Jittered));
+        let offsets = r.subpixel_offsets(10, 20);
+        assert_eq!(offsets.len(), 16);
+        for (i, (dx, dy)) in offsets.iter().enumerate() {
+            let (u, v) = ((i % 4) as f64, (i / 4) as f64);
+            assert!((u * 0.25..(u + 1.) * 0.25).contains(dx), "{dx}");
+            assert!((v * 0.25..(v + 1.) * 0.25).contains(dy), "{dy}");
+        }
+        assert_eq!(offsets, r.subpixel_offsets(10, 20));
+    }
+
+    #[test]
+    fn supersampling_averages_the_samples() {
+        let (w, c) = (World::default(), test_camera());
+        let r = Renderer::new(&c, &w, with_sampling(4, SampleMode::Stratified));
+        let expected = r
+            .subpixel_offsets(5, 5)
+            .iter()
+            .map(|(dx, dy)| r.sample(5, 5, *dx, *dy))
+            .fold(BLACK, |sum, col| sum + col)
+            * 0.25;
+        assert_eq!(r.pixel_color(5, 5), expected);
+        // the edge of the sphere gets blended with the background
+        let single = Renderer::new(&c, &w, RenderSettings::default());
+        assert_ne!(r.pixel_color(5, 4), single.pixel_color(5, 4));
+    }
+
+    // adaptive sampling
+    fn adaptive(threshold: f64, max_depth: u8) -> RenderSettings {
+        with_sampling(
+            1,
+            SampleMode::Adaptive {
+                threshold,
+                max_depth,
+            },
+        )
+    }
+
+    #[test]
+    fn adaptive_sampling_averages_the_corners_of_flat_pixels() {
+        let (w, c) = (World::default(), test_camera());
+        // nothing differs more than the threshold
+        let r = Renderer::new(&c, &w, adaptive(10., 3));
+        let corner = |dx, dy| r.sample(5, 5, dx, dy);
+        let expected = (corner(0., 0.) + corner(1., 0.) + corner(0., 1.) + corner(1., 1.)) * 0.25;
+        assert_eq!(r.pixel_color(5, 5), expected);
+        // background only
+        assert_eq!(r.pixel_color(0, 0), BLACK);
+    }
+
+    #[test]
+    fn adaptive_sampling_refines_the_edges() {
+        let (w, c) = (World::default(), test_camera());
+        let flat = Renderer::new(&c, &w, adaptive(0.1, 0));
+        let refined = Renderer::new(&c, &w, adaptive(0.1, 3));
+        // the edge of the sphere
+        assert_ne!(flat.pixel_color(5, 4), refined.pixel_color(5, 4));
+        // the background is flat, so nothing changes there
+        assert_eq!(flat.pixel_color(1, 1), refined.pixel_color(1, 1));
+    }
+
+    #[test]
+    fn adaptive_rows_match_single_pixels() {
+        let (w, c) = (World::default(), test_camera());
+        let r = Renderer::new(&c, &w, adaptive(0.05, 2));
+        for y in [0, 4, 5] {
+            let row = r.row_colors(y);
+            for (x, col) in row.iter().enumerate() {
+                assert_eq!(*col, r.pixel_color(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_rows_match_single_pixels_with_depth_of_field() {
+        // the lens point of a sample must not depend on which pixel shares that corner
+        let w = World::default();
+        let c = test_camera().with_depth_of_field(0.4, 5.0);
+        let r = Renderer::new(&c, &w, adaptive(0.05, 2));
+        for y in [0, 4, 5] {
+            let row = r.row_colors(y);
+            for (x, col) in row.iter().enumerate() {
+                assert_eq!(*col, r.pixel_color(x, y), "({x}, {y})");
+            }
+        }
+    }
+
+    // tiles
+    #[test]
+    fn tiles_cover_the_whole_image() {
+        let (w, c) = (World::default(), test_camera());
+        let settings = RenderSettings {
+            tile_size: 4,
+            ..Default::default()
+        };
+        let tiles = Renderer::new(&c, &w, settings).tiles();
+        let tile = |x0, y0, width, height| Tile {
+            x0,
+            y0,
+            width,
+            height,
+        };
+        assert_eq!(tiles.len(), 9);
+        assert_eq!(tiles[0], tile(0, 0, 4, 4));
+        assert_eq!(tiles[2], tile(8, 0, 3, 4));
+        assert_eq!(tiles[8], tile(8, 8, 3, 3));
+        let area: usize = tiles.iter().map(|t| t.width * t.height).sum();
+        assert_eq!(area, 11 * 11);
+    }
+
+    #[test]
+    fn a_region_matches_the_same_pixels_of_the_full_image() {
+        let (w, c) = (World::default(), test_camera());
+        let r = Renderer::new(&c, &w, RenderSettings::default());
+        let full = r.render();
+        let block = r.render_region(3, 4, 5, 2);
+        assert_eq!((block.width, block.height), (5, 2));
+        for y in 0..2 {
+            assert_eq!(block[y], full[4 + y][3..8]);
+        }
+        // gets cut off at the edge of the image
+        let block = r.render_region(8, 9, 5, 5);
+        assert_eq!((block.width, block.height), (3, 2));
+        assert_eq!(block[1], full[10][8..11]);
+    }
+
+    #[test]
+    fn stitching_tiles_back_together() {
+        let (w, c) = (World::default(), test_camera());
+        let settings = RenderSettings {
+            tile_size: 4,
+            ..Default::default()
+        };
+        let r = Renderer::new(&c, &w, settings);
+        // in reverse, the order does not matter
+        let blocks = r
+            .tiles()
+            .iter()
+            .rev()
+            .map(|t| (t.x0, t.y0, r.render_tile(t)))
+            .collect::<Vec<_>>();
+        let stitched = Canvas::stitch(11, 11, blocks);
+        assert_eq!(stitched.arr, r.render().arr);
+    }
+
+    #[test]
+    fn tile_size_does_not_change_the_image() {
+        let (w, c) = (World::default(), test_camera());
+        for sample_mode in [
+            SampleMode::Jittered,
+            SampleMode::Adaptive {
+                threshold: 0.1,
+                max_depth: 2,
+            },
+        ] {
+            let settings = |tile_size| RenderSettings {
+                tile_size,
+                sample_mode,
+                samples: 4,
+                ..Default::default()
+            };
+            let big = Renderer::new(&c, &w, settings(100)).render();
+            let small = Renderer::new(&c, &w, settings(3)).render();
+            assert_eq!(big.arr, small.arr);
+        }
+    }
+
+    #[test]
+    fn progress_gets_told_about_every_tile() {
+        #[derive(Default)]
+        struct Counter {
+            total: AtomicUsize,
+            done: AtomicUsize,
+            finished: AtomicUsize,
+        }
+        impl Progress for Counter {
+            fn start(&self, tiles: usize) {
+                self.total.store(tiles, Ordering::SeqCst);
+            }
+            fn tile_done(&self) {
+                self.done.fetch_add(1, Ordering::SeqCst);
+            }
+            fn finish(&self) {
+                self.finished.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        let (w, c) = (World::default(), test_camera());
+        let settings = RenderSettings {
+            tile_size: 5,
+            ..Default::default()
+        };
+        let counter = Counter::default();
+        Renderer::new(&c, &w, settings).render_with_progress(&counter);
+        assert_eq!(counter.total.load(Ordering::SeqCst), 9);
+        assert_eq!(counter.done.load(Ordering::SeqCst), 9);
+        assert_eq!(counter.finished.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn a_cancelled_token_skips_all_tiles() {
+        let (w, c) = (World::default(), test_camera());
+        let token = RenderToken::new();
+        token.cancel();
+        let image = Renderer::new(&c, &w, RenderSettings::default()).render_with_progress(&token);
+        assert!(image.arr.iter().flatten().all(|col| *col == BLACK));
+        assert_eq!(token.tiles_done(), 0);
+        assert_eq!(token.tiles_total(), 1);
+    }
+
+    #[test]
+    fn stopping_early_returns_the_finished_tiles() {
+        // stops itself after the third tile
+        struct StopAfter3(RenderToken);
+        impl Progress for StopAfter3 {
+            fn start(&self, tiles: usize) {
+                self.0.start(tiles);
+            }
+            fn tile_done(&self) {
+                self.0.tile_done();
+                if self.0.tiles_done() >= 3 {
+                    self.0.cancel();
+                }
+            }
+            fn should_stop(&self) -> bool {
+                self.0.should_stop()
+            }
+        }
+        let (w, c) = (World::default(), test_camera());
+        let settings = RenderSettings {
+            tile_size: 1,
+            ..Default::default()
+        };
+        let r = Renderer::new(&c, &w, settings);
+        let stop = StopAfter3(RenderToken::new());
+        let partial = r.render_with_progress(&stop);
+        let full = r.render();
+        // with rayon a few tiles might have already been started on other threads
+        assert!((3..11 * 11).contains(&stop.0.tiles_done()));
+        assert_ne!(partial.arr, full.arr);
+        // whatever got rendered is the same as in the full image
+        for (partial, full) in partial.arr.iter().flatten().zip(full.arr.iter().flatten()) {
+            assert!(*partial == BLACK || partial == full);
+        }
+    }
+
+    // progressive rendering
+    #[test]
+    fn every_pass_uses_other_subpixels() {
+        let (w, c) = (World::default(), test_camera());
+        let r = Renderer::new(&c, &w, with_sampling(1, SampleMode::Stratified));
+        assert_eq!(r.for_pass(0).subpixel_offsets(2, 3), vec![(0.5, 0.5)]);
+        let (p1, p2) = (
+            r.for_pass(1).subpixel_offsets(2, 3),
+            r.for_pass(2).subpixel_offsets(2, 3),
+        );
+        assert_ne!(p1, p2);
+        assert_eq!(p1, r.for_pass(1).subpixel_offsets(2, 3));
+    }
+
+    #[test]
+    fn progressive_rendering_averages_the_passes() {
+        let (w, c) = (World::default(), test_camera());
+        let settings = RenderSettings {
+            passes: 3,
+            ..Default::default()
+        };
+        let r = Renderer::new(&c, &w, settings);
+        let mut seen = vec![];
+        let image = r.render_progressive(&NoProgress, |passes, image| {
+            seen.push((passes, image.clone()));
+        });
+        assert_eq!(seen.len(), 3);
+        assert_eq!(seen[0].0, 1);
+        // the first pass is just a normal render
+        assert_eq!(seen[0].1.arr, r.render().arr);
+        assert_eq!(seen[2].1.arr, image.arr);
+        let average = (r.pixel_color(4, 5)
+            + r.for_pass(1).pixel_color(4, 5)
+            + r.for_pass(2).pixel_color(4, 5))
+            * (1. / 3.);
+        assert_eq!(image[5][4], average);
+    }
+
+    #[test]
+    fn stopping_progressive_rendering_keeps_the_finished_passes() {
+        // stops when the second pass starts
+        struct StopInPass2(RenderToken);
+        impl Progress for StopInPass2 {
+            fn start(&self, tiles: usize) {
+                if self.0.tiles_total() > 0 {
+                    self.0.cancel();
+                }
+                self.0.start(tiles);
+            }
+            fn should_stop(&self) -> bool {
+                self.0.should_stop()
+            }
+        }
+        let (w, c) = (World::default(), test_camera());
+        let settings = RenderSettings {
+            passes: 10,
+            ..Default::default()
+        };
+        let r = Renderer::new(&c, &w, settings);
+        let mut passes = 0;
+        let image = r.render_progressive(&StopInPass2(RenderToken::new()), |p, _| passes = p);
+        assert_eq!(passes, 1);
+        assert_eq!(image.arr, r.render().arr);
+    }
+}
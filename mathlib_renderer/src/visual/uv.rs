@@ -0,0 +1,308 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::mathstructs::point::Point;
+
+use super::{canvas::Canvas, color::Col};
+
+/// how a point on (or in) an object gets flattened into (u, v) coordinates, both between 0 and 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UvMapping {
+    /// around the unit sphere, like a globe. u goes around the y axis, v from the bottom to the top
+    Spherical,
+    /// the xz plane, repeating every unit
+    Planar,
+    /// around the y axis like Spherical, but v repeats every unit of y
+    Cylindrical,
+    /// every face of the unit cube gets its own part of the image. The image is an unfolded cube:
+    /// ```text
+    ///       | up    |
+    /// | left| front | right | back |
+    ///       | down  |
+    /// ```
+    Cube,
+}
+
+impl UvMapping {
+    pub fn uv(&self, point: &Point) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => spherical_map(point),
+            UvMapping::Planar => planar_map(point),
+            UvMapping::Cylindrical => cylindrical_map(point),
+            UvMapping::Cube => cube_map(point),
+        }
+    }
+}
+
+/// how the image gets sampled between its pixels
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Filter {
+    /// the closest pixel. Blocky, when the image is small
+    Nearest,
+    /// mixes the 4 closest pixels
+    #[default]
+    Bilinear,
+}
+
+/// an image that gets looked up with (u, v). (0, 0) is the bottom left, (1, 1) the top right.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvImage {
+    /// shared, so cloning the pattern does not copy the whole image
+    pub canvas: Arc<Canvas>,
+    pub filter: Filter,
+}
+
+impl UvImage {
+    pub fn new(canvas: Canvas, filter: Filter) -> Self {
+        Self {
+            canvas: Arc::new(canvas),
+            filter,
+        }
+    }
+
+    pub fn at(&self, u: f64, v: f64) -> Col {
+        let canvas = &self.canvas;
+        if canvas.width == 0 || canvas.height == 0 {
+            return Col::new(0., 0., 0.);
+        }
+        // the rows of the canvas start at the top
+        let x = u.clamp(0., 1.) * (canvas.width - 1) as f64;
+        let y = (1. - v.clamp(0., 1.)) * (canvas.height - 1) as f64;
+        match self.filter {
+            Filter::Nearest => *canvas.read(x.round() as usize, y.round() as usize),
+            Filter::Bilinear => {
+                let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+                let (x1, y1) = (
+                    (x0 + 1).min(canvas.width - 1),
+                    (y0 + 1).min(canvas.height - 1),
+                );
+                let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+                let top = lerp(*canvas.read(x0, y0), *canvas.read(x1, y0), tx);
+                let bottom = lerp(*canvas.read(x0, y1), *canvas.read(x1, y1), tx);
+                lerp(top, bottom, ty)
+            }
+        }
+    }
+}
+
+fn lerp(a: Col, b: Col, t: f64) -> Col {
+    a + (b - a) * t
+}
+
+fn spherical_map(point: &Point) -> (f64, f64) {
+    let radius = (point.x * point.x + point.y * point.y + point.z * point.z).sqrt();
+    if radius == 0. {
+        return (0.5, 0.5);
+    }
+    let phi = (point.y / radius).clamp(-1., 1.).acos();
+    (around_y(point), 1. - phi / PI)
+}
+
+fn planar_map(point: &Point) -> (f64, f64) {
+    (point.x.rem_euclid(1.), point.z.rem_euclid(1.))
+}
+
+fn cylindrical_map(point: &Point) -> (f64, f64) {
+    (around_y(point), point.y.rem_euclid(1.))
+}
+
+/// 0..1 counterclockwise (seen from above), starting at -z
+fn around_y(point: &Point) -> f64 {
+    let theta = point.x.atan2(point.z);
+    1. - (theta / (2. * PI) + 0.5)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Face {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+/// the face of the cube the point is on: the one of the biggest coordinate
+fn face_from_point(point: &Point) -> Face {
+    let coord = point.x.abs().max(point.y.abs()).max(point.z.abs());
+    match coord {
+        c if c == point.x => Face::Right,
+        c if c == -point.x => Face::Left,
+        c if c == point.y => Face::Up,
+        c if c == -point.y => Face::Down,
+        c if c == point.z => Face::Front,
+        _ => Face::Back,
+    }
+}
+
+/// (u, v) on the face itself, both 0..1
+fn face_uv(face: Face, point: &Point) -> (f64, f64) {
+    let (x, y, z) = (point.x, point.y, point.z);
+    let half = |value: f64| value.rem_euclid(2.) / 2.;
+    match face {
+        Face::Front => (half(x + 1.), half(y + 1.)),
+        Face::Back => (half(1. - x), half(y + 1.)),
+        Face::Left => (half(z + 1.), half(y + 1.)),
+        Face::Right => (half(1. - z), half(y + 1.)),
+        Face::Up => (half(x + 1.), half(1. - z)),
+        Face::Down => (half(x + 1.), half(z + 1.)),
+    }
+}
+
+fn cube_map(point: &Point) -> (f64, f64) {
+    let face = face_from_point(point);
+    let (u, v) = face_uv(face, point);
+    // (column, row) of the face in the unfolded cube, rows counted from the bottom
+    let (column, row) = match face {
+        Face::Left => (0., 1.),
+        Face::Front => (1., 1.),
+        Face::Right => (2., 1.),
+        Face::Back => (3., 1.),
+        Face::Up => (1., 2.),
+        Face::Down => (1., 0.),
+    };
+    ((column + u) / 4., (row + v) / 3.)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visual::color::{BLACK, WHITE};
+
+    use super::*;
+
+    fn assert_uv(mapping: UvMapping, point: Point, expected: (f64, f64)) {
+        let (u, v) = mapping.uv(&point);
+        assert!(
+            (u - expected.0).abs() < 1e-9 && (v - expected.1).abs() < 1e-9,
+            "{mapping:?} {point:?}: ({u}, {v}) instead of {expected:?}"
+        );
+    }
+
+    #[test]
+    fn using_a_spherical_mapping_on_a_3d_point() {
+        let half = 2_f64.sqrt() / 2.;
+        for (point, uv) in [
+            (Point::new(0., 0., -1.), (0.0, 0.5)),
+            (Point::new(1., 0., 0.), (0.25, 0.5)),
+            (Point::new(0., 0., 1.), (0.5, 0.5)),
+            (Point::new(-1., 0., 0.), (0.75, 0.5)),
+            (Point::new(0., 1., 0.), (0.5, 1.0)),
+            (Point::new(0., -1., 0.), (0.5, 0.0)),
+            (Point::new(half, half, 0.), (0.25, 0.75)),
+        ] {
+            assert_uv(UvMapping::Spherical, point, uv);
+        }
+    }
+
+    #[test]
+    fn using_a_planar_mapping_on_a_3d_point() {
+        for (point, uv) in [
+            (Point::new(0.25, 0., 0.5), (0.25, 0.5)),
+            (Point::new(0.25, 0., -0.25), (0.25, 0.75)),
+            (Point::new(0.25, 0.5, -0.25), (0.25, 0.75)),
+            (Point::new(1.25, 0., 0.5), (0.25, 0.5)),
+            (Point::new(0.25, 0., -1.75), (0.25, 0.25)),
+            (Point::new(1., 0., -1.), (0.0, 0.0)),
+            (Point::new(0., 0., 0.), (0.0, 0.0)),
+        ] {
+            assert_uv(UvMapping::Planar, point, uv);
+        }
+    }
+
+    #[test]
+    fn using_a_cylindrical_mapping_on_a_3d_point() {
+        let half = 2_f64.sqrt() / 2.;
+        for (point, uv) in [
+            (Point::new(0., 0., -1.), (0.0, 0.0)),
+            (Point::new(0., 0.5, -1.), (0.0, 0.5)),
+            (Point::new(0., 1., -1.), (0.0, 0.0)),
+            (Point::new(half, 0.5, -half), (0.125, 0.5)),
+            (Point::new(1., 0.5, 0.), (0.25, 0.5)),
+            (Point::new(half, 0.5, half), (0.375, 0.5)),
+            (Point::new(0., -0.25, 1.), (0.5, 0.75)),
+            (Point::new(-half, 0.5, half), (0.625, 0.5)),
+            (Point::new(-1., 1.25, 0.), (0.75, 0.25)),
+            (Point::new(-half, 0.5, -half), (0.875, 0.5)),
+        ] {
+            assert_uv(UvMapping::Cylindrical, point, uv);
+        }
+    }
+
+    #[test]
+    fn identifying_the_face_of_a_cube_from_a_point() {
+        for (point, face) in [
+            (Point::new(-1., 0.5, -0.25), Face::Left),
+            (Point::new(1.1, -0.75, 0.8), Face::Right),
+            (Point::new(0.1, 0.6, 0.9), Face::Front),
+            (Point::new(-0.7, 0., -2.), Face::Back),
+            (Point::new(0.5, 1., 0.9), Face::Up),
+            (Point::new(-0.2, -1.3, 1.1), Face::Down),
+        ] {
+            assert_eq!(face_from_point(&point), face);
+        }
+    }
+
+    #[test]
+    fn uv_mapping_the_faces_of_a_cube() {
+        for (face, point, uv) in [
+            (Face::Front, Point::new(-0.5, 0.5, 1.), (0.25, 0.75)),
+            (Face::Front, Point::new(0.5, -0.5, 1.), (0.75, 0.25)),
+            (Face::Back, Point::new(0.5, 0.5, -1.), (0.25, 0.75)),
+            (Face::Back, Point::new(-0.5, -0.5, -1.), (0.75, 0.25)),
+            (Face::Left, Point::new(-1., 0.5, -0.5), (0.25, 0.75)),
+            (Face::Left, Point::new(-1., -0.5, 0.5), (0.75, 0.25)),
+            (Face::Right, Point::new(1., 0.5, 0.5), (0.25, 0.75)),
+            (Face::Right, Point::new(1., -0.5, -0.5), (0.75, 0.25)),
+            (Face::Up, Point::new(-0.5, 1., -0.5), (0.25, 0.75)),
+            (Face::Up, Point::new(0.5, 1., 0.5), (0.75, 0.25)),
+            (Face::Down, Point::new(-0.5, -1., 0.5), (0.25, 0.75)),
+            (Face::Down, Point::new(0.5, -1., -0.5), (0.75, 0.25)),
+        ] {
+            assert_eq!(face_uv(face, &point), uv, "{face:?}");
+        }
+    }
+
+    #[test]
+    fn cube_mapping_picks_the_part_of_the_unfolded_cube() {
+        // the middle of every face
+        for (point, uv) in [
+            (Point::new(-1., 0., 0.), (0.125, 0.5)),
+            (Point::new(0., 0., 1.), (0.375, 0.5)),
+            (Point::new(1., 0., 0.), (0.625, 0.5)),
+            (Point::new(0., 0., -1.), (0.875, 0.5)),
+            (Point::new(0., 1., 0.), (0.375, 2.5 / 3.)),
+            (Point::new(0., -1., 0.), (0.375, 0.5 / 3.)),
+        ] {
+            assert_uv(UvMapping::Cube, point, uv);
+        }
+    }
+
+    #[test]
+    fn sampling_an_image_with_nearest_filtering() {
+        let mut canvas = Canvas::new(10, 10);
+        for y in 0..10 {
+            for x in 0..10 {
+                canvas.write_px(x, y, Col::new(x as f64 / 10., y as f64 / 10., 0.));
+            }
+        }
+        let image = UvImage::new(canvas, Filter::Nearest);
+        // v = 0 is the bottom row of the image
+        assert_eq!(image.at(0., 0.), Col::new(0., 0.9, 0.));
+        assert_eq!(image.at(1., 1.), Col::new(0.9, 0., 0.));
+        assert_eq!(image.at(0.3, 0.6), Col::new(0.3, 0.4, 0.));
+        assert_eq!(image.at(0.4, 0.1), Col::new(0.4, 0.8, 0.));
+    }
+
+    #[test]
+    fn sampling_an_image_with_bilinear_filtering() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write_px(0, 0, WHITE).write_px(1, 1, WHITE);
+        let image = UvImage::new(canvas, Filter::Bilinear);
+        assert_eq!(image.at(0., 1.), WHITE);
+        assert_eq!(image.at(1., 1.), BLACK);
+        assert_eq!(image.at(0.5, 0.5), Col::new(0.5, 0.5, 0.5));
+        assert_eq!(image.at(0.25, 1.), Col::new(0.75, 0.75, 0.75));
+        assert_eq!(image.at(0., 0.75), Col::new(0.75, 0.75, 0.75));
+        // outside of the image is the edge
+        assert_eq!(image.at(-3., 7.), WHITE);
+    }
+}
@@ -1,16 +1,56 @@
 use crate::{
     cmp::ApproxEq,
     mathstructs::{matrix::Matrix, point::Point},
-    object::{sphere::Sphere, Object},
+    object::{group::Group, sphere::Sphere, Object},
     ray::{computations::Computations, intersects::VecIntersections, Ray},
 };
 
 use super::{
     color::{Col, BLACK},
-    light::Light,
+    light::{Light, LightSample},
     patterns::Pattern,
+    render::RenderSettings,
 };
 
+/// groups with at least this many children get split up further by build_bvh()
+const BVH_THRESHOLD: usize = 4;
+
+/// how much further a ray can bounce around (reflections and refractions)
+#[derive(Debug, Clone, Copy)]
+struct Trace {
+    remaining: u8,
+    /// how much the color of the current ray still contributes to the pixel
+    weight: f64,
+    min_weight: f64,
+}
+
+impl Trace {
+    fn new(remaining: u8, min_weight: f64) -> Self {
+        Self {
+            remaining,
+            weight: 1.0,
+            min_weight,
+        }
+    }
+
+    /// trace for a ray spawned of the current one, that contributes factor of its color.
+    /// None if we are too deep already or the ray would not matter enough.
+    fn bounce(&self, factor: f64) -> Option<Self> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let weight = self.weight * factor;
+        if weight < self.min_weight {
+            return None;
+        }
+        Some(Self {
+            remaining: self.remaining - 1,
+            weight,
+            min_weight: self.min_weight,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct World {
     pub lights: Vec<Light>,
@@ -25,7 +65,16 @@ impl World {
         }
     }
 
-    fn intersect_world(&self, ray: &Ray) -> VecIntersections {
+    /// puts all objects into a bounding volume hierarchy. So every ray only has to be tested
+    /// against objects whose bounds it actually passes through, instead of against every object.
+    /// - call this again after adding objects
+    pub fn build_bvh(&mut self) {
+        let mut root = Group::new(std::mem::take(&mut self.objects));
+        root.divide(BVH_THRESHOLD);
+        self.objects = vec![root];
+    }
+
+    fn intersect_world(&self, ray: &Ray) -> VecIntersections<'_> {
         let mut intersections = VecIntersections::new();
         for obj in &self.objects {
             intersections.intersect_add(ray, obj);
@@ -45,7 +94,7 @@ impl Default for World {
         objects.push(s1);
 
         let mut s2 = Sphere::new();
-        s2.transformation = Matrix::scaling_new(0.5, 0.5, 0.5);
+        s2.set_transform(Matrix::scaling_new(0.5, 0.5, 0.5));
         objects.push(s2);
 
         Self {
@@ -57,26 +106,28 @@ impl Default for World {
 
 impl World {
     /// for every light source we sum up all the colors and return the sum
-    fn shade_hit(&self, comps: &Computations, remaining: u8) -> Col {
+    fn shade_hit(&self, comps: &Computations, trace: Trace) -> Col {
         // multiple lights exist in the secene (careful many will slow down everything)
         let mut col_sum = BLACK;
         for cur_light in &self.lights {
-            // calculate and add the surface color
+            // calculate and add the surface color.
+            // - over_point for both, so jittered area lights shade with the same samples
+            //   they got tested for shadows with (the samples are seeded by the point)
             col_sum = col_sum
                 + Light::lighting(
                     &comps.object.material,
                     &comps.object,
                     cur_light,
-                    &comps.point,
+                    &comps.over_point,
                     &comps.eye_v,
                     &comps.normal_v,
-                    self.is_shadowed(&comps.over_point, cur_light),
+                    self.intensity_at(&comps.over_point, cur_light),
                 );
         }
 
         // calculate and add reflected light
-        let reflected = self.reflected_color(comps, remaining);
-        let refracted = self.refracted_color(comps, remaining);
+        let reflected = self.reflected_color_traced(comps, trace);
+        let refracted = self.refracted_color_traced(comps, trace);
         if comps.object.material.reflective > 0. && comps.object.material.transparency > 0. {
             let reflectance = comps.schlick();
             return col_sum + reflected * reflectance + refracted * (1. - reflectance);
@@ -84,17 +135,25 @@ impl World {
         col_sum + reflected + refracted
     }
 
-    /// cast ray to the lightsource, if we hit any obstruction => were in the shadow of that
-    fn is_shadowed(&self, point: &Point, current_light: &Light) -> bool {
-        let v = current_light.position - *point;
-        let distance = v.magnitude();
-        let direction = v.normalize();
+    /// fraction of the light that reaches the point. 0 is fully in shadow, 1 is fully lit.
+    /// - for area lights only some of the samples might be blocked, which makes the soft shadows
+    pub fn intensity_at(&self, point: &Point, light: &Light) -> f64 {
+        let samples = light.samples_from(point);
+        let lit = samples
+            .iter()
+            .filter(|sample| !self.is_shadowed(point, sample))
+            .count();
+        lit as f64 / samples.len() as f64
+    }
 
-        let ray = Ray::new(*point, direction);
+    /// cast ray to the lightsource, if we hit any obstruction => were in the shadow of that
+    /// - directional lights are infinitely far away, so anything in the way casts a shadow
+    fn is_shadowed(&self, point: &Point, sample: &LightSample) -> bool {
+        let ray = Ray::new(*point, sample.direction);
         let intersections = self.intersect_world(&ray);
         let hit = intersections.hit();
         if let Some(h) = hit {
-            if h.t < distance {
+            if h.t < sample.distance {
                 return true;
             }
         }
@@ -102,12 +161,21 @@ impl World {
     }
 
     pub fn color_at(&self, ray: &Ray, remaining: u8) -> Col {
+        self.color_at_traced(ray, Trace::new(remaining, 0.0))
+    }
+
+    /// color_at() with the depth and early termination of the scene's settings
+    pub fn color_with_settings(&self, ray: &Ray, settings: &RenderSettings) -> Col {
+        self.color_at_traced(ray, Trace::new(settings.max_depth, settings.min_weight))
+    }
+
+    fn color_at_traced(&self, ray: &Ray, trace: Trace) -> Col {
         let intersects = self.intersect_world(ray);
         match intersects.hit() {
             None => BLACK,
             Some(i) => {
                 let comps = Computations::prepare(&i, ray);
-                self.shade_hit(&comps, remaining)
+                self.shade_hit(&comps, trace)
             }
         }
     }
@@ -115,24 +183,34 @@ impl World {
     /// reflections in reflective surfaces
     /// we basically spawn new rays from reflective material recursively
     pub fn reflected_color(&self, comps: &Computations, remaining: u8) -> Col {
-        if remaining == 0 || comps.object.material.reflective.apx_eq(&0.0) {
+        self.reflected_color_traced(comps, Trace::new(remaining, 0.0))
+    }
+
+    fn reflected_color_traced(&self, comps: &Computations, trace: Trace) -> Col {
+        if comps.object.material.reflective.apx_eq(&0.0) {
             return BLACK;
         }
+        let Some(next) = trace.bounce(comps.object.material.reflective) else {
+            return BLACK;
+        };
         // we use the over_point to make sure we dont rounding-error to inside the shape we bounce off
         let reflect_ray = Ray::new(comps.over_point, comps.reflective_v);
-        let color = self.color_at(&reflect_ray, remaining - 1);
+        let color = self.color_at_traced(&reflect_ray, next);
         color * comps.object.material.reflective
     }
 
     /// see-trough materials
     pub fn refracted_color(&self, comps: &Computations, remaining: u8) -> Col {
-        #[allow(clippy::absurd_extreme_comparisons)]
-        if remaining <= 0 {
-            return BLACK;
-        }
+        self.refracted_color_traced(comps, Trace::new(remaining, 0.0))
+    }
+
+    fn refracted_color_traced(&self, comps: &Computations, trace: Trace) -> Col {
         if comps.object.material.transparency == 0. {
             return BLACK;
         }
+        let Some(next) = trace.bounce(comps.object.material.transparency) else {
+            return BLACK;
+        };
 
         // total internal reflection (ex inside a fiber-optic-cable or with shallow angle against water surface)
         let n_ratio = comps.n1 / comps.n2;
@@ -146,7 +224,7 @@ impl World {
         let cos_t = (1. - sin2_t).sqrt();
         let direction = comps.normal_v * (n_ratio * cos_i - cos_t) - comps.eye_v * n_ratio;
         let refract_ray = Ray::new(comps.under_point, direction);
-        self.color_at(&refract_ray, remaining - 1) * comps.object.material.transparency
+        self.color_at_traced(&refract_ray, next) * comps.object.material.transparency
     }
 }
 
@@ -178,7 +256,7 @@ mod tests {
         s1.material.specular = 0.2;
 
         let mut s2 = Sphere::new();
-        s2.transformation = Matrix::scaling_new(0.5, 0.5, 0.5);
+        s2.set_transform(Matrix::scaling_new(0.5, 0.5, 0.5));
 
         assert_eq!(w.lights.len(), 1);
         assert_eq!(w.lights[0], light);
@@ -208,7 +286,7 @@ mod tests {
         let shape = w.objects.first().unwrap();
         let i = Intersect::new(4.0, shape);
         let comps = Computations::prepare(&i, &r);
-        let c = w.shade_hit(&comps, 1);
+        let c = w.shade_hit(&comps, Trace::new(1, 0.));
         assert_eq!(c, Col::new(0.38066, 0.47583, 0.2855));
     }
 
@@ -222,7 +300,7 @@ mod tests {
         let i = Intersect::new(0.5, shape);
         let comps = Computations::prepare(&i, &r);
 
-        let c = w.shade_hit(&comps, 1);
+        let c = w.shade_hit(&comps, Trace::new(1, 0.));
         assert_eq!(c, Col::new(0.90498, 0.90498, 0.90498));
     }
 
@@ -262,32 +340,36 @@ mod tests {
     fn there_is_no_shadow_when_nothing_is_collinear_with_point_and_light() {
         let w = World::default();
         let p = Point::inew(0, 10, 0);
-        let current_light = &w.lights[0];
-        assert_eq!(w.is_shadowed(&p, current_light), false);
+        let light_position = w.lights[0].position().unwrap();
+        let sample = LightSample::towards(&p, &light_position);
+        assert_eq!(w.is_shadowed(&p, &sample), false);
     }
 
     #[test]
     fn the_shadow_when_an_object_is_between_the_point_and_the_light() {
         let w = World::default();
         let p = Point::inew(10, -10, 10);
-        let current_light = &w.lights[0];
-        assert_eq!(w.is_shadowed(&p, current_light), true);
+        let light_position = w.lights[0].position().unwrap();
+        let sample = LightSample::towards(&p, &light_position);
+        assert_eq!(w.is_shadowed(&p, &sample), true);
     }
 
     #[test]
     fn there_is_no_shadow_when_and_object_is_behind_the_light() {
         let w = World::default();
         let p = Point::inew(-20, 20, -20);
-        let current_light = &w.lights[0];
-        assert_eq!(w.is_shadowed(&p, current_light), false);
+        let light_position = w.lights[0].position().unwrap();
+        let sample = LightSample::towards(&p, &light_position);
+        assert_eq!(w.is_shadowed(&p, &sample), false);
     }
 
     #[test]
     fn there_is_no_shadow_when_an_object_is_behind_the_point() {
         let w = World::default();
         let p = Point::inew(-2, 2, -2);
-        let current_light = &w.lights[0];
-        assert_eq!(w.is_shadowed(&p, current_light), false);
+        let light_position = w.lights[0].position().unwrap();
+        let sample = LightSample::towards(&p, &light_position);
+        assert_eq!(w.is_shadowed(&p, &sample), false);
     }
 
     #[test]
@@ -297,12 +379,12 @@ mod tests {
         let s1 = Sphere::new();
         w.objects.push(s1);
         let mut s2 = Sphere::new();
-        s2.transformation = Matrix::translation_new(0.0, 0.0, 10.0);
+        s2.set_transform(Matrix::translation_new(0.0, 0.0, 10.0));
         w.objects.push(s2);
         let ray = Ray::new(Point::inew(0, 0, 5), Vector::inew(0, 0, 1));
         let intersection = Intersect::new(4.0, &w.objects[1]);
         let comps = Computations::prepare(&intersection, &ray);
-        let c = w.shade_hit(&comps, 1);
+        let c = w.shade_hit(&comps, Trace::new(1, 0.));
         assert_eq!(c, Col::new(0.1, 0.1, 0.1));
     }
 
@@ -324,7 +406,7 @@ mod tests {
         let mut w = World::default();
         let mut shape = Plane::new();
         shape.material.reflective = 0.5;
-        shape.transformation = Matrix::translation_new(0.0, -1.0, 0.0);
+        shape.set_transform(Matrix::translation_new(0.0, -1.0, 0.0));
         w.objects.push(shape);
 
         let sq = 2.0_f64.sqrt() / 2.0;
@@ -341,7 +423,7 @@ mod tests {
         let mut w = World::default();
         let mut shape = Plane::new();
         shape.material.reflective = 0.5;
-        shape.transformation = Matrix::translation_new(0.0, -1.0, 0.0);
+        shape.set_transform(Matrix::translation_new(0.0, -1.0, 0.0));
         w.objects.push(shape);
 
         let sq = 2.0_f64.sqrt() / 2.0;
@@ -349,7 +431,7 @@ mod tests {
         let shape = w.objects.last_mut().unwrap();
         let i = Intersect::new(2.0_f64.sqrt(), &shape);
         let comps = Computations::prepare(&i, &ray);
-        let res = w.shade_hit(&comps, 1);
+        let res = w.shade_hit(&comps, Trace::new(1, 0.));
         // these values were off quite a bit from the book's. There might be
         // a bug somewhere       0.87677,  0.92436 , 0.82918
         assert_eq!(res, Col::new(0.876757, 0.924340, 0.82918));
@@ -362,12 +444,12 @@ mod tests {
 
         let mut lower = Plane::new();
         lower.material.reflective = 1.;
-        lower.transformation = Matrix::translation_new(0., -1., 0.);
+        lower.set_transform(Matrix::translation_new(0., -1., 0.));
         w.objects[0] = lower;
 
         let mut upper = Plane::new();
         upper.material.reflective = 1.;
-        upper.transformation = Matrix::translation_new(0., 1., 0.);
+        upper.set_transform(Matrix::translation_new(0., 1., 0.));
         w.objects[1] = upper;
 
         let ray = Ray::new(Point::inew(0, 0, 0), Vector::inew(0, 1, 0));
@@ -381,7 +463,7 @@ mod tests {
         let mut w = World::default();
         let mut shape = Plane::new();
         shape.material.reflective = 0.5;
-        shape.transformation = Matrix::translation_new(0.0, -1.0, 0.0);
+        shape.set_transform(Matrix::translation_new(0.0, -1.0, 0.0));
         w.objects.push(shape);
 
         let sq = 2.0_f64.sqrt() / 2.0;
@@ -401,25 +483,9 @@ mod tests {
         let shape = w.objects.first().unwrap();
         let ray = Ray::new(Point::new(0., 0., -5.), Vector::new(0., 0., 1.));
         let xs = VecIntersections {
-            0: vec![
-                Intersect {
-                    t: 4.,
-                    object: &shape,
-                },
-                Intersect {
-                    t: 6.,
-                    object: &shape,
-                },
-            ],
+            0: vec![Intersect::new(4., &shape), Intersect::new(6., &shape)],
         };
-        let comps = Computations::prepare_computations(
-            &Intersect {
-                t: 4.,
-                object: &shape,
-            },
-            &ray,
-            &xs,
-        );
+        let comps = Computations::prepare_computations(&Intersect::new(4., &shape), &ray, &xs);
         let c = w.refracted_color(&comps, 5);
         assert_eq!(c, BLACK);
     }
@@ -432,25 +498,9 @@ mod tests {
         shape.material.refractive_index = 1.5;
         let ray = Ray::new(Point::new(0., 0., -5.), Vector::new(0., 0., 1.));
         let xs = VecIntersections {
-            0: vec![
-                Intersect {
-                    t: 4.,
-                    object: &shape,
-                },
-                Intersect {
-                    t: 6.,
-                    object: &shape,
-                },
-            ],
+            0: vec![Intersect::new(4., &shape), Intersect::new(6., &shape)],
         };
-        let comps = Computations::prepare_computations(
-            &Intersect {
-                t: 4.,
-                object: &shape,
-            },
-            &ray,
-            &xs,
-        );
+        let comps = Computations::prepare_computations(&Intersect::new(4., &shape), &ray, &xs);
         let c = w.refracted_color(&comps, 0);
         assert_eq!(c, BLACK);
     }
@@ -464,25 +514,9 @@ mod tests {
         let sq = 2.0_f64.sqrt() / 2.;
         let ray = Ray::new(Point::new(0., 0., sq), Vector::new(0., 1., 0.));
         let xs = VecIntersections {
-            0: vec![
-                Intersect {
-                    t: -sq,
-                    object: &shape,
-                },
-                Intersect {
-                    t: sq,
-                    object: &shape,
-                },
-            ],
+            0: vec![Intersect::new(-sq, &shape), Intersect::new(sq, &shape)],
         };
-        let comps = Computations::prepare_computations(
-            &Intersect {
-                t: sq,
-                object: &shape,
-            },
-            &ray,
-            &xs,
-        );
+        let comps = Computations::prepare_computations(&Intersect::new(sq, &shape), &ray, &xs);
         let c = w.refracted_color(&comps, 5);
         assert_eq!(c, BLACK);
     }
@@ -504,32 +538,14 @@ mod tests {
         let ray = Ray::new(Point::new(0., 0., 0.1), Vector::new(0., 1., 0.));
         let xs = VecIntersections {
             0: vec![
-                Intersect {
-                    t: -0.9899,
-                    object: &a_shape,
-                },
-                Intersect {
-                    t: -0.4899,
-                    object: &b_shape,
-                },
-                Intersect {
-                    t: 0.4899,
-                    object: &b_shape,
-                },
-                Intersect {
-                    t: 0.9899,
-                    object: &a_shape,
-                },
+                Intersect::new(-0.9899, &a_shape),
+                Intersect::new(-0.4899, &b_shape),
+                Intersect::new(0.4899, &b_shape),
+                Intersect::new(0.9899, &a_shape),
             ],
         };
-        let comps = Computations::prepare_computations(
-            &Intersect {
-                t: 0.4899,
-                object: &b_shape,
-            },
-            &ray,
-            &xs,
-        );
+        let comps =
+            Computations::prepare_computations(&Intersect::new(0.4899, &b_shape), &ray, &xs);
         let c = w.refracted_color(&comps, 5);
         assert_eq!(c, Col::new(0., 0.99888, 0.04721));
         // book has different rounding here: 0. 0.99888 0.04725
@@ -552,20 +568,10 @@ mod tests {
         let ray = Ray::new(Point::new(0., 0., -3.), Vector::new(0., -sq / 2., sq / 2.));
         let obj = &w.objects[2];
         let xs = VecIntersections {
-            0: vec![Intersect {
-                t: sq,
-                object: &obj,
-            }],
+            0: vec![Intersect::new(sq, &obj)],
         };
-        let comps = Computations::prepare_computations(
-            &Intersect {
-                t: sq,
-                object: &obj,
-            },
-            &ray,
-            &xs,
-        );
-        let c = w.shade_hit(&comps, 5);
+        let comps = Computations::prepare_computations(&Intersect::new(sq, &obj), &ray, &xs);
+        let c = w.shade_hit(&comps, Trace::new(5, 0.));
         assert_eq!(c, Col::new(0.93642, 0.68642, 0.68642));
     }
 
@@ -587,20 +593,184 @@ mod tests {
         let ray = Ray::new(Point::new(0., 0., -3.), Vector::new(0., -sq / 2., sq / 2.));
         let obj = &w.objects[2];
         let xs = VecIntersections {
-            0: vec![Intersect {
-                t: sq,
-                object: &obj,
-            }],
+            0: vec![Intersect::new(sq, &obj)],
         };
-        let comps = Computations::prepare_computations(
-            &Intersect {
-                t: sq,
-                object: &obj,
-            },
-            &ray,
-            &xs,
-        );
-        let c = w.shade_hit(&comps, 5);
+        let comps = Computations::prepare_computations(&Intersect::new(sq, &obj), &ray, &xs);
+        let c = w.shade_hit(&comps, Trace::new(5, 0.));
         assert_eq!(c, Col::new(0.93391, 0.69643, 0.69243));
     }
+
+    #[test]
+    fn bvh_does_not_change_the_rendered_colors() {
+        let mut w = World::default();
+        for x in -3..3 {
+            w.objects
+                .push(Sphere::new().with_transform(Matrix::translation_new(x as f64 * 3., 0., 4.)));
+        }
+        w.objects
+            .push(Plane::new().with_transform(Matrix::translation_new(0., -1., 0.)));
+        let mut bvh = w.clone();
+        bvh.build_bvh();
+        assert_eq!(bvh.objects.len(), 1);
+        for (x, y) in [
+            (0., 0.),
+            (0.3, 0.2),
+            (-3., 0.),
+            (6., -0.5),
+            (2., -0.9),
+            (0., 5.),
+        ] {
+            let ray = Ray::new(Point::new(x, y, -5.), Vector::new(0., 0., 1.));
+            assert_eq!(w.color_at(&ray, 4), bvh.color_at(&ray, 4));
+        }
+    }
+
+    #[test]
+    fn point_lights_evaluate_the_light_intensity_at_a_given_point() {
+        let w = World::default();
+        let light = &w.lights[0];
+        let table = [
+            (Point::inew(0, 10, 0), 1.0),
+            (Point::inew(10, -10, 10), 0.0),
+            (Point::inew(-20, 20, -20), 1.0),
+            (Point::inew(-2, 2, -2), 1.0),
+        ];
+        for (point, expected) in table {
+            assert_eq!(w.intensity_at(&point, light), expected);
+        }
+    }
+
+    #[test]
+    fn the_area_light_intensity_function() {
+        let w = World::default();
+        let light = Light::new_area_light(
+            Point::new(-0.5, -0.5, -5.),
+            Vector::inew(1, 0, 0),
+            2,
+            Vector::inew(0, 1, 0),
+            2,
+            Col::new(1., 1., 1.),
+            false,
+        );
+        let table = [
+            (Point::inew(0, 0, 2), 0.0),
+            (Point::new(1., -1., 2.), 0.25),
+            (Point::new(1.5, 0., 2.), 0.5),
+            (Point::new(1.25, 1.25, 3.), 0.75),
+            (Point::inew(0, 0, -2), 1.0),
+        ];
+        for (point, expected) in table {
+            assert_eq!(w.intensity_at(&point, &light), expected);
+        }
+    }
+
+    #[test]
+    fn jittered_area_lights_shade_with_the_samples_of_the_shadow_test() {
+        let mut w = World::default();
+        let light = Light::new_area_light(
+            Point::new(-1., 1., -5.),
+            Vector::inew(2, 0, 0),
+            4,
+            Vector::inew(0, 2, 0),
+            4,
+            Col::new(1., 1., 1.),
+            true,
+        );
+        w.lights = vec![light.clone()];
+        let r = Ray::new(Point::inew(0, 0, -5), Vector::inew(0, 0, 1));
+        let shape = w.objects.first().unwrap();
+        let comps = Computations::prepare(&Intersect::new(4.0, shape), &r);
+        let shade = |point: &Point| {
+            Light::lighting(
+                &shape.material,
+                shape,
+                &light,
+                point,
+                &comps.eye_v,
+                &comps.normal_v,
+                w.intensity_at(&comps.over_point, &light),
+            )
+        };
+        // the jitter is seeded by the point, so point and over_point get different samples
+        assert_ne!(shade(&comps.point), shade(&comps.over_point));
+        assert_eq!(
+            w.shade_hit(&comps, Trace::new(1, 0.)),
+            shade(&comps.over_point)
+        );
+    }
+
+    #[test]
+    fn directional_light_shadows_do_not_depend_on_distance() {
+        let w = World::default();
+        let light = Light::new_directional_light(Vector::inew(0, -1, 0), Col::new(1., 1., 1.));
+        // the spheres at the origin are far below, but still block the 'sun'
+        assert_eq!(w.intensity_at(&Point::inew(0, -100, 0), &light), 0.0);
+        assert_eq!(w.intensity_at(&Point::inew(5, -100, 0), &light), 1.0);
+        assert_eq!(w.intensity_at(&Point::inew(0, 100, 0), &light), 1.0);
+    }
+
+    #[test]
+    fn spot_lights_cast_shadows_like_point_lights() {
+        let w = World::default();
+        let light = Light::new_spot_light(
+            Point::inew(0, 10, 0),
+            Vector::inew(0, -1, 0),
+            0.5,
+            1.0,
+            Col::new(1., 1., 1.),
+        );
+        assert_eq!(w.intensity_at(&Point::inew(0, -5, 0), &light), 0.0);
+        assert_eq!(w.intensity_at(&Point::inew(0, 5, 0), &light), 1.0);
+    }
+
+    // render settings
+    #[test]
+    fn rays_that_contribute_too_little_are_not_followed() {
+        let mut w = World::default();
+        let mut shape = Plane::new();
+        shape.material.reflective = 0.5;
+        shape.set_transform(Matrix::translation_new(0.0, -1.0, 0.0));
+        w.objects.push(shape);
+
+        let sq = 2.0_f64.sqrt() / 2.0;
+        let ray = Ray::new(Point::inew(0, 0, -3), Vector::new(0.0, -sq, sq));
+        let with_reflection = RenderSettings {
+            max_depth: 5,
+            min_weight: 0.1,
+            ..Default::default()
+        };
+        let without_reflection = RenderSettings {
+            max_depth: 5,
+            min_weight: 0.6,
+            ..Default::default()
+        };
+        let no_depth = RenderSettings {
+            max_depth: 0,
+            min_weight: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            w.color_with_settings(&ray, &with_reflection),
+            w.color_at(&ray, 5)
+        );
+        assert_eq!(
+            w.color_with_settings(&ray, &without_reflection),
+            w.color_at(&ray, 0)
+        );
+        assert_eq!(w.color_with_settings(&ray, &no_depth), w.color_at(&ray, 0));
+        assert_ne!(w.color_at(&ray, 5), w.color_at(&ray, 0));
+    }
+
+    #[test]
+    fn trace_weight_shrinks_with_every_bounce() {
+        let trace = Trace::new(2, 0.2);
+        let next = trace.bounce(0.5).unwrap();
+        assert_eq!((next.remaining, next.weight), (1, 0.5));
+        let next = next.bounce(0.5).unwrap();
+        assert_eq!((next.remaining, next.weight), (0, 0.25));
+        // out of depth
+        assert!(next.bounce(1.0).is_none());
+        // too little weight
+        assert!(trace.bounce(0.1).is_none());
+    }
 }
@@ -0,0 +1,274 @@
+use std::fmt;
+
+use yaml_rust2::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+    yaml, ScanError, Yaml,
+};
+
+/// what went wrong while turning the yaml into a scene
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneErrorKind {
+    /// not even valid yaml
+    Syntax(String),
+    /// valid yaml, but not shaped like a scene. Like an entry that is not a mapping.
+    Structure(String),
+    /// a value we have no support for, like 'add: teapot' or 'type: laser'
+    Unknown(String),
+    /// a key that has to be there is missing
+    MissingKey,
+    /// the value has the wrong type or is out of range. Holds what we expected instead.
+    InvalidValue(String),
+    /// a definition that does not exist (or got used before it was defined)
+    UnknownDefinition(String),
+    MissingCamera,
+    MissingLight,
+    /// a file the scene references could not be read
+    Io(String),
+}
+
+/// error of SceneToRun::new_from_yaml. Knows where in the yaml it happened (as far as we can tell).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneError {
+    pub kind: SceneErrorKind,
+    /// the top level entry the error is in, like 'add: light' or 'define: white-material'
+    pub node: Option<String>,
+    /// the key whose value is the problem
+    pub key: Option<String>,
+    /// the keys (and '[index]' of list items) the key is nested in, from the entry down.
+    /// Like ["material", "pattern"] for the transform of a pattern.
+    /// - boxed, so results with a SceneError stay small
+    pub path: Box<[String]>,
+    /// (line, column) in the yaml. Both start at 1.
+    pub position: Option<(usize, usize)>,
+}
+
+impl SceneError {
+    pub fn new(kind: SceneErrorKind) -> Self {
+        Self {
+            kind,
+            node: None,
+            key: None,
+            path: Box::default(),
+            position: None,
+        }
+    }
+
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    pub fn missing(key: &str) -> Self {
+        Self::new(SceneErrorKind::MissingKey).with_key(key)
+    }
+
+    /// expected: what the value should have been instead, like "3 numbers"
+    pub fn invalid(key: &str, expected: &str) -> Self {
+        Self::new(SceneErrorKind::InvalidValue(expected.to_string())).with_key(key)
+    }
+
+    pub fn unknown(key: &str, value: &str) -> Self {
+        Self::new(SceneErrorKind::Unknown(value.to_string())).with_key(key)
+    }
+
+    /// the error happened inside the value of the key (or list item '[index]')
+    pub(crate) fn inside(mut self, key: &str) -> Self {
+        let mut path = self.path.into_vec();
+        path.insert(0, key.to_string());
+        self.path = path.into_boxed_slice();
+        self
+    }
+
+    /// fills in the top level entry the error happened in and where that (or its key) is.
+    /// Does not overwrite what is already known.
+    pub(crate) fn in_entry(
+        mut self,
+        index: usize,
+        node: &yaml::Hash,
+        positions: &Positions,
+    ) -> Self {
+        if self.node.is_none() {
+            self.node = entry_name(node);
+        }
+        if self.position.is_none() {
+            let mut path = self.path.to_vec();
+            path.extend(self.key.clone());
+            self.position = positions.find(index, &path);
+        }
+        self
+    }
+}
+
+/// 'add: light', 'define: white-material'...
+fn entry_name(node: &yaml::Hash) -> Option<String> {
+    ["add", "define"].iter().find_map(|keyword| {
+        let value = node.get(&Yaml::from_str(keyword))?;
+        match value.as_str() {
+            Some(name) => Some(format!("{keyword}: {name}")),
+            None => Some(keyword.to_string()),
+        }
+    })
+}
+
+impl From<ScanError> for SceneError {
+    fn from(err: ScanError) -> Self {
+        let marker = err.marker();
+        Self {
+            position: Some((marker.line(), marker.col() + 1)),
+            ..Self::new(SceneErrorKind::Syntax(err.info().to_string()))
+        }
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((line, col)) = self.position {
+            write!(f, "line {line}, column {col}: ")?;
+        }
+        let key = self.key.as_deref().unwrap_or("?");
+        match &self.kind {
+            SceneErrorKind::Syntax(info) => write!(f, "invalid yaml: {info}")?,
+            SceneErrorKind::Structure(info) => write!(f, "{info}")?,
+            SceneErrorKind::Unknown(value) => write!(f, "unknown '{value}' for '{key}'")?,
+            SceneErrorKind::MissingKey => write!(f, "missing '{key}:'")?,
+            SceneErrorKind::InvalidValue(expected) => write!(f, "expected {expected} for '{key}'")?,
+            SceneErrorKind::UnknownDefinition(name) => write!(
+                f,
+                "definition '{name}' not found, it must be defined before it gets used"
+            )?,
+            SceneErrorKind::MissingCamera => write!(f, "expected a camera to be 'add'-ed")?,
+            SceneErrorKind::MissingLight => {
+                write!(f, "expected at least one light to be 'add'-ed")?
+            }
+            SceneErrorKind::Io(info) => write!(f, "{info}")?,
+        }
+        match &self.node {
+            Some(node) => write!(f, " (in '{node}')"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// where each top level entry and the keys inside of it are in the yaml.
+/// The YamlLoader forgets about that, so we go over the events of the parser ourself.
+#[derive(Debug, Default)]
+pub(crate) struct Positions {
+    entries: Vec<EntryPositions>,
+    /// the sequences and mappings we are currently inside of
+    frames: Vec<Frame>,
+}
+
+#[derive(Debug)]
+struct EntryPositions {
+    start: Marker,
+    /// every key (and list item) inside the entry, with the path to it. Like
+    /// ["material", "pattern", "colors", "[1]"] for the second color of a pattern.
+    keys: Vec<(Vec<String>, Marker)>,
+}
+
+#[derive(Debug)]
+enum Frame {
+    /// index of the item we are in
+    Sequence { index: Option<usize> },
+    /// keys and values take turns. key is the one whose value we are in
+    Mapping {
+        expect_key: bool,
+        key: Option<String>,
+    },
+}
+
+impl Frame {
+    /// how the path names what is inside of this frame right now
+    fn segment(&self) -> Option<String> {
+        match self {
+            Frame::Sequence { index } => index.map(|index| format!("[{index}]")),
+            Frame::Mapping { key, .. } => key.clone(),
+        }
+    }
+}
+
+impl Positions {
+    /// syntax errors get reported by the YamlLoader, so we just take what we got till then
+    pub fn new(yaml_str: &str) -> Self {
+        let mut positions = Self::default();
+        let _ = Parser::new_from_str(yaml_str).load(&mut positions, false);
+        positions
+    }
+
+    /// (line, column) of the key at that path in the entry. If it is not there (like when the
+    /// value is the name of a definition), of the deepest key on the way. Else of the entry itself.
+    pub fn find(&self, entry: usize, path: &[String]) -> Option<(usize, usize)> {
+        let entry = self.entries.get(entry)?;
+        let marker = (1..=path.len())
+            .rev()
+            .find_map(|len| entry.keys.iter().find(|(keys, _)| keys[..] == path[..len]))
+            .map_or(&entry.start, |(_, marker)| marker);
+        // the parser counts columns from 0
+        Some((marker.line(), marker.col() + 1))
+    }
+}
+
+impl MarkedEventReceiver for Positions {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        if let Event::Scalar(..)
+        | Event::Alias(_)
+        | Event::SequenceStart(..)
+        | Event::MappingStart(..) = ev
+        {
+            if let [Frame::Sequence { .. }] = self.frames[..] {
+                self.entries.push(EntryPositions {
+                    start: mark,
+                    keys: vec![],
+                });
+            }
+            let is_key = match self.frames.last_mut() {
+                Some(Frame::Sequence { index }) => {
+                    *index = Some(index.map_or(0, |index| index + 1));
+                    false
+                }
+                Some(Frame::Mapping { expect_key, .. }) => {
+                    *expect_key = !*expect_key;
+                    !*expect_key
+                }
+                None => false,
+            };
+            let key = match (&ev, is_key) {
+                (Event::Scalar(key, ..), true) => Some(key.clone()),
+                _ => None,
+            };
+            let list_item = matches!(self.frames.last(), Some(Frame::Sequence { .. }));
+            if let (Some(key), Some(Frame::Mapping { key: current, .. })) =
+                (&key, self.frames.last_mut())
+            {
+                *current = Some(key.clone());
+            }
+            // the first frame is the list of entries, the second one the entry itself
+            if let (true, true, Some(entry)) = (
+                self.frames.len() >= 2,
+                key.is_some() || list_item,
+                self.entries.last_mut(),
+            ) {
+                // mappings only point to where their first key starts
+                if entry.keys.is_empty() {
+                    entry.start = mark;
+                }
+                let path = self.frames[1..].iter().filter_map(Frame::segment).collect();
+                entry.keys.push((path, mark));
+            }
+        }
+        match ev {
+            Event::SequenceStart(..) => self.frames.push(Frame::Sequence { index: None }),
+            Event::MappingStart(..) => self.frames.push(Frame::Mapping {
+                expect_key: true,
+                key: None,
+            }),
+            Event::SequenceEnd | Event::MappingEnd => {
+                self.frames.pop();
+            }
+            _ => (),
+        }
+    }
+}
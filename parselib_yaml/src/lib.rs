@@ -1 +1,3 @@
-pub mod yaml;
\ No newline at end of file
+pub mod error;
+pub mod obj;
+pub mod yaml;
@@ -0,0 +1,389 @@
+use std::fmt;
+
+use mathlib_renderer::{
+    mathstructs::{point::Point, vector::Vector},
+    object::{group::Group, smooth_triangle::SmoothTriangle, triangle::Triangle, Object},
+    visual::material::Material,
+};
+
+/// Holds all triangles parsed out of a Wavefront OBJ file.
+/// - supported are vertices `v`, vertex normals `vn`, faces `f` and named groups `g`
+/// - everything else (texture-vertices, materials, comments...) just gets counted as ignored
+pub struct ObjParser {
+    pub vertices: Vec<Point>,
+    pub normals: Vec<Vector>,
+    /// triangles that got added before any `g` line
+    pub default_group: Vec<Object>,
+    /// triangles of each named group in the order they appear in the file
+    pub named_groups: Vec<(String, Vec<Object>)>,
+    pub ignored_lines: usize,
+}
+
+/// a face we can not turn into triangles. Lines that are just broken get ignored instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjError {
+    /// starts at 1
+    pub line: usize,
+    pub kind: ObjErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjErrorKind {
+    /// holds how many the face had
+    TooFewVertices(usize),
+    /// the index of the face that does not exist (yet)
+    UnknownVertex(String),
+    UnknownNormal(String),
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ObjErrorKind::TooFewVertices(count) => {
+                write!(f, "face has {count} vertices, expected at least 3")
+            }
+            ObjErrorKind::UnknownVertex(corner) => {
+                write!(f, "face references unknown vertex '{corner}'")
+            }
+            ObjErrorKind::UnknownNormal(corner) => {
+                write!(f, "face references unknown normal '{corner}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl ObjParser {
+    /// Parses the content of an OBJ file. Polygons with more than 3 vertices get fan-triangulated.
+    pub fn new_from_str(obj_str: &str) -> Result<Self, ObjError> {
+        let mut parser = ObjParser {
+            vertices: vec![],
+            normals: vec![],
+            default_group: vec![],
+            named_groups: vec![],
+            ignored_lines: 0,
+        };
+        for (i, line) in obj_str.lines().enumerate() {
+            let error = |kind| ObjError { line: i + 1, kind };
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("v") => match three_f64(words) {
+                    Some((x, y, z)) => parser.vertices.push(Point::new(x, y, z)),
+                    None => parser.ignored_lines += 1,
+                },
+                Some("vn") => match three_f64(words) {
+                    Some((x, y, z)) => parser.normals.push(Vector::new(x, y, z)),
+                    None => parser.ignored_lines += 1,
+                },
+                Some("f") => {
+                    let triangles = parser.fan_triangulation(words.collect()).map_err(error)?;
+                    parser.current_group().extend(triangles);
+                }
+                Some("g") => {
+                    let name = words.collect::<Vec<_>>().join(" ");
+                    parser.named_groups.push((name, vec![]));
+                }
+                _ => parser.ignored_lines += 1,
+            }
+        }
+        Ok(parser)
+    }
+
+    /// Combines all parsed triangles into one group. Each named group becomes a subgroup.
+    pub fn into_group(self, material: &Material) -> Object {
+        let with_material = |triangles: Vec<Object>| -> Vec<Object> {
+            triangles
+                .into_iter()
+                .map(|mut t| {
+                    t.material = material.clone();
+                    t
+                })
+                .collect()
+        };
+        let mut children = with_material(self.default_group);
+        for (_, triangles) in self.named_groups {
+            children.push(Group::new(with_material(triangles)));
+        }
+        Group::new(children)
+    }
+
+    /// triangles of a group with the given name (first match)
+    pub fn named_group(&self, name: &str) -> Option<&[Object]> {
+        self.named_groups
+            .iter()
+            .find(|(group_name, _)| group_name == name)
+            .map(|(_, triangles)| triangles.as_slice())
+    }
+
+    fn current_group(&mut self) -> &mut Vec<Object> {
+        match self.named_groups.last_mut() {
+            Some((_, triangles)) => triangles,
+            None => &mut self.default_group,
+        }
+    }
+
+    // f 1 2 3 4       -> vertices only
+    // f 1/2/3 2/3/4   -> vertex/texture/normal
+    // f 1//3 2//4     -> vertex//normal
+    fn fan_triangulation(&self, face: Vec<&str>) -> Result<Vec<Object>, ObjErrorKind> {
+        let corners = face
+            .iter()
+            .map(|corner| self.parse_corner(corner))
+            .collect::<Result<Vec<_>, _>>()?;
+        if corners.len() < 3 {
+            return Err(ObjErrorKind::TooFewVertices(corners.len()));
+        }
+        let triangles = (1..corners.len() - 1)
+            .map(|i| {
+                let (p1, n1) = corners[0];
+                let (p2, n2) = corners[i];
+                let (p3, n3) = corners[i + 1];
+                match (n1, n2, n3) {
+                    (Some(n1), Some(n2), Some(n3)) => SmoothTriangle::new(p1, p2, p3, n1, n2, n3),
+                    _ => Triangle::new(p1, p2, p3),
+                }
+            })
+            .collect();
+        Ok(triangles)
+    }
+
+    fn parse_corner(&self, corner: &str) -> Result<(Point, Option<Vector>), ObjErrorKind> {
+        let mut indices = corner.split('/');
+        let vertex = indices
+            .next()
+            .and_then(|idx| resolve_index(idx, self.vertices.len()))
+            .map(|idx| self.vertices[idx])
+            .ok_or_else(|| ObjErrorKind::UnknownVertex(corner.to_string()))?;
+        // texture-vertices are not supported, so we skip those
+        let normal = match indices.nth(1).filter(|idx| !idx.is_empty()) {
+            Some(idx) => Some(
+                resolve_index(idx, self.normals.len())
+                    .map(|idx| self.normals[idx])
+                    .ok_or_else(|| ObjErrorKind::UnknownNormal(corner.to_string()))?,
+            ),
+            None => None,
+        };
+        Ok((vertex, normal))
+    }
+}
+
+/// obj indices start at 1. Negative indices count backwards from the last element.
+fn resolve_index(idx: &str, len: usize) -> Option<usize> {
+    let idx: i64 = idx.parse().ok()?;
+    let resolved = match idx {
+        i if i > 0 => i - 1,
+        i if i < 0 => len as i64 + i,
+        _ => return None,
+    };
+    (0..len as i64)
+        .contains(&resolved)
+        .then_some(resolved as usize)
+}
+
+fn three_f64<'a>(mut words: impl Iterator<Item = &'a str>) -> Option<(f64, f64, f64)> {
+    let mut next = || words.next()?.parse::<f64>().ok();
+    Some((next()?, next()?, next()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use mathlib_renderer::object::Shape;
+
+    use super::*;
+
+    fn triangle(obj: &Object) -> &Triangle {
+        match obj.shape() {
+            Shape::Triangle(t) => t,
+            Shape::SmoothTriangle(t) => &t.triangle,
+            _ => panic!("expected a triangle, got {:?}", obj.shape()),
+        }
+    }
+
+    #[test]
+    fn ignoring_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright
+who traveled much faster than light.
+She set out one day
+in a relative way,
+and came back the previous night.";
+        let parser = ObjParser::new_from_str(gibberish).unwrap();
+        assert_eq!(parser.ignored_lines, 5);
+    }
+
+    #[test]
+    fn vertex_records() {
+        let file = "v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0";
+        let parser = ObjParser::new_from_str(file).unwrap();
+        assert_eq!(parser.vertices[0], Point::new(-1., 1., 0.));
+        assert_eq!(parser.vertices[1], Point::new(-1., 0.5, 0.));
+        assert_eq!(parser.vertices[2], Point::new(1., 0., 0.));
+        assert_eq!(parser.vertices[3], Point::new(1., 1., 0.));
+    }
+
+    #[test]
+    fn parsing_triangle_faces() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 3
+f 1 3 4";
+        let parser = ObjParser::new_from_str(file).unwrap();
+        let t1 = triangle(&parser.default_group[0]);
+        let t2 = triangle(&parser.default_group[1]);
+        assert_eq!(t1.p1, parser.vertices[0]);
+        assert_eq!(t1.p2, parser.vertices[1]);
+        assert_eq!(t1.p3, parser.vertices[2]);
+        assert_eq!(t2.p1, parser.vertices[0]);
+        assert_eq!(t2.p2, parser.vertices[2]);
+        assert_eq!(t2.p3, parser.vertices[3]);
+    }
+
+    #[test]
+    fn triangulating_polygons() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+v 0 2 0
+
+f 1 2 3 4 5";
+        let parser = ObjParser::new_from_str(file).unwrap();
+        assert_eq!(parser.default_group.len(), 3);
+        for (i, obj) in parser.default_group.iter().enumerate() {
+            let t = triangle(obj);
+            assert_eq!(t.p1, parser.vertices[0]);
+            assert_eq!(t.p2, parser.vertices[i + 1]);
+            assert_eq!(t.p3, parser.vertices[i + 2]);
+        }
+    }
+
+    #[test]
+    fn triangles_in_groups() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+        let parser = ObjParser::new_from_str(file).unwrap();
+        let g1 = parser.named_group("FirstGroup").unwrap();
+        let g2 = parser.named_group("SecondGroup").unwrap();
+        assert_eq!(triangle(&g1[0]).p2, parser.vertices[1]);
+        assert_eq!(triangle(&g2[0]).p2, parser.vertices[2]);
+        assert!(parser.default_group.is_empty());
+    }
+
+    #[test]
+    fn converting_an_obj_file_to_a_group() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+f 1 2 3
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+        let material = Material {
+            reflective: 0.5,
+            ..Default::default()
+        };
+        let group = ObjParser::new_from_str(file).unwrap().into_group(&material);
+        let children = group.children();
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[0].material, material);
+        assert_eq!(children[1].children().len(), 1);
+        assert_eq!(children[2].children()[0].material, material);
+    }
+
+    #[test]
+    fn vertex_normal_records() {
+        let file = "vn 0 0 1
+vn 0.707 0 -0.707
+vn 1 2 3";
+        let parser = ObjParser::new_from_str(file).unwrap();
+        assert_eq!(parser.normals[0], Vector::new(0., 0., 1.));
+        assert_eq!(parser.normals[1], Vector::new(0.707, 0., -0.707));
+        assert_eq!(parser.normals[2], Vector::new(1., 2., 3.));
+    }
+
+    #[test]
+    fn faces_with_normals() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+
+vn -1 0 0
+vn 1 0 0
+vn 0 1 0
+
+f 1//3 2//1 3//2
+f 1/0/3 2/102/1 -1/14/-2";
+        let parser = ObjParser::new_from_str(file).unwrap();
+        let expected = SmoothTriangle::from_points(
+            parser.vertices[0],
+            parser.vertices[1],
+            parser.vertices[2],
+            parser.normals[2],
+            parser.normals[0],
+            parser.normals[1],
+        );
+        for obj in &parser.default_group {
+            assert_eq!(obj.shape(), &Shape::SmoothTriangle(expected.clone()));
+        }
+    }
+
+    #[test]
+    fn faces_need_3_vertices() {
+        let file = "v 0 1 0
+v -1 0 0
+
+f 1 2";
+        let err = ObjParser::new_from_str(file).err().unwrap();
+        assert_eq!(
+            err,
+            ObjError {
+                line: 4,
+                kind: ObjErrorKind::TooFewVertices(2)
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 4: face has 2 vertices, expected at least 3"
+        );
+    }
+
+    #[test]
+    fn faces_can_only_use_existing_vertices_and_normals() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+vn 0 1 0
+f 1 2 4";
+        let err = ObjParser::new_from_str(file).err().unwrap();
+        assert_eq!(err.line, 5);
+        assert_eq!(err.kind, ObjErrorKind::UnknownVertex("4".to_string()));
+
+        let err = ObjParser::new_from_str("v 0 1 0\nf 0 -1 1\n")
+            .err()
+            .unwrap();
+        assert_eq!(err.kind, ObjErrorKind::UnknownVertex("0".to_string()));
+
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+vn 0 1 0
+f 1//1 2//1 3//2";
+        let err = ObjParser::new_from_str(file).err().unwrap();
+        assert_eq!(err.kind, ObjErrorKind::UnknownNormal("3//2".to_string()));
+    }
+}
@@ -1,74 +1,223 @@
 use std::collections::HashMap;
 
 use mathlib_renderer::{
+    cmp::ApproxEq,
     mathstructs::{matrix::Matrix, point::Point, vector::Vector},
-    object::{cube::Cube, plane::Plane, sphere::Sphere, Object},
+    noise::Fractal,
+    object::{
+        cone::Cone,
+        csg::{Csg, CsgOperation},
+        cube::Cube,
+        cylinder::Cylinder,
+        group::Group,
+        plane::Plane,
+        sphere::Sphere,
+        Object,
+    },
     visual::{
-        camera::Camera,
+        camera::{Camera, Projection},
+        canvas::Canvas,
         color::{Col, WHITE},
-        light::Light,
-        material::Material, world::World,
+        light::{Attenuation, Light},
+        material::Material,
+        patterns::Pattern,
+        render::{RenderSettings, SampleMode},
+        uv::{Filter, UvImage, UvMapping},
+        world::World,
     },
 };
 use yaml_rust2::{yaml, Yaml, YamlLoader};
 
+use crate::{
+    error::{Positions, SceneError, SceneErrorKind},
+    obj::ObjParser,
+};
+
 /// Container holding all information relevant to a scene.
 pub struct SceneToRun {
     pub camera: Camera,
     pub world: World,
+    pub settings: RenderSettings,
 }
 
 impl SceneToRun {
     /// Parses all information regarding the scene out of a yaml-string.
-    pub fn new_from_yaml(yaml_str: &str) -> Self {
-        let docs = YamlLoader::load_from_str(&yaml_str).expect("Unable to load yaml from string.");
-        let root_nodes = docs[0]
-            .as_vec()
-            .expect("Bad yaml structure. Expected multiple entries.");
-    
-        let defs = parse_definitions(root_nodes);
-    
+    /// - errors point to the entry (and key) that is the problem
+    pub fn new_from_yaml(yaml_str: &str) -> Result<Self, SceneError> {
+        let docs = YamlLoader::load_from_str(yaml_str)?;
+        let positions = Positions::new(yaml_str);
+        let root_nodes = docs
+            .first()
+            .and_then(|doc| doc.as_vec())
+            .ok_or_else(|| structure_error("expected a list of entries like '- add: camera'"))?;
+        let root_nodes = root_nodes
+            .iter()
+            .enumerate()
+            .map(|(index, yaml)| {
+                yaml.as_hash().ok_or_else(|| SceneError {
+                    position: positions.find(index, &[]),
+                    ..structure_error("expected each entry to be a mapping like 'add: camera'")
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let defs = parse_definitions(&root_nodes, &positions)?;
+
         let mut camera: Option<Camera> = None;
+        let mut settings = RenderSettings::default();
         let mut lights: Vec<Light> = vec![];
         let mut objects: Vec<Object> = vec![];
-    
-        for node in root_nodes.iter().map(|yaml| yaml.as_hash().unwrap()) {
+
+        for (index, node) in root_nodes.iter().enumerate() {
+            let in_entry = |err: SceneError| err.in_entry(index, node, &positions);
             if let Some(add_node) = node.get(&Yaml::from_str("add")) {
-                match add_node.as_str().unwrap() {
-                    "camera" => camera = Some(camera_from_node(node)),
-                    "light" => lights.push(light_from_node(node)),
-                    typ if typ == "cube" || typ == "plane" || typ == "sphere" => {
-                        objects.push(obj_from_node(node, typ, &defs))
+                match add_node.as_str() {
+                    Some("camera") => {
+                        camera = Some(camera_from_node(node).map_err(in_entry)?);
+                        sampling_from_node(node, &mut settings).map_err(in_entry)?;
+                    }
+                    Some("settings") => {
+                        settings_from_node(node, &mut settings).map_err(in_entry)?
+                    }
+                    Some("light") => lights.push(light_from_node(node).map_err(in_entry)?),
+                    Some(
+                        typ @ ("cube" | "plane" | "sphere" | "cylinder" | "cone" | "group" | "obj"
+                        | "csg"),
+                    ) => objects.push(obj_from_node(node, typ, &defs).map_err(in_entry)?),
+                    Some(typ) => return Err(in_entry(SceneError::unknown("add", typ))),
+                    None => {
+                        return Err(in_entry(SceneError::invalid("add", "a type like 'sphere'")))
                     }
-                    _ => unimplemented!("missing support for type {}", add_node.as_str().unwrap()),
                 }
             }
         }
-    
-        if lights.len() < 1 {
-            panic!("Expect at least one light to be 'add'-ed.");
+
+        if lights.is_empty() {
+            return Err(SceneError::new(SceneErrorKind::MissingLight));
         }
         // dbg!(&objects);
-        SceneToRun {
-            camera: camera.expect("Expected one camera to be 'add-ed."),
-            world: World { lights, objects }
-        }
+        let mut world = World { lights, objects };
+        world.build_bvh();
+        Ok(SceneToRun {
+            camera: camera.ok_or(SceneError::new(SceneErrorKind::MissingCamera))?,
+            world,
+            settings,
+        })
     }
 }
 
-fn obj_from_node(node: &yaml::Hash, type_name: &str, defs: &Definitions) -> Object {
+fn structure_error(info: &str) -> SceneError {
+    SceneError::new(SceneErrorKind::Structure(info.to_string()))
+}
+
+fn obj_from_node(
+    node: &yaml::Hash,
+    type_name: &str,
+    defs: &Definitions,
+) -> Result<Object, SceneError> {
+    let material = || material_from_node(defs, node).map_err(|err| err.inside("material"));
     let mut object = match type_name {
         "cube" => Cube::new(),
         "plane" => Plane::new(),
         "sphere" => Sphere::new(),
-        _ => {
-            unimplemented!()
-        }
+        "cylinder" => Cylinder::new_truncated(
+            f64_from_key(node, "min")?.unwrap_or(f64::NEG_INFINITY),
+            f64_from_key(node, "max")?.unwrap_or(f64::INFINITY),
+            bool_from_key(node, "closed")?.unwrap_or(false),
+        ),
+        "cone" => Cone::new_truncated(
+            f64_from_key(node, "min")?.unwrap_or(f64::NEG_INFINITY),
+            f64_from_key(node, "max")?.unwrap_or(f64::INFINITY),
+            bool_from_key(node, "closed")?.unwrap_or(false),
+        ),
+        "group" => Group::new(children_from_node(node, defs)?),
+        "csg" => csg_from_node(node, defs)?,
+        // the material gets applied to every triangle of the mesh instead
+        "obj" => obj_file_from_node(node, &material()?)?,
+        typ => return Err(SceneError::unknown("add", typ)),
+    };
+    object.material = material()?;
+    object.set_transform(transform_from_node(defs, node)?);
+
+    Ok(object)
+}
+
+// - add: group
+//   transform:
+//     - [ translate, 0, 1, 0 ]
+//   children:
+//     - add: sphere
+//     - add: cube
+//       transform:
+//         - [ translate, 2, 0, 0 ]
+fn children_from_node(node: &yaml::Hash, defs: &Definitions) -> Result<Vec<Object>, SceneError> {
+    let Some(children_node) = node.get(&Yaml::from_str("children")) else {
+        return Ok(vec![]);
+    };
+    children_node
+        .as_vec()
+        .ok_or_else(|| SceneError::invalid("children", "a list of objects"))?
+        .iter()
+        .enumerate()
+        .map(|(i, child)| {
+            child_from_node(child, "children", defs)
+                .map_err(|err| err.inside(&format!("[{i}]")).inside("children"))
+        })
+        .collect()
+}
+
+// - add: csg
+//   operation: difference      # union | intersection | difference
+//   left:
+//     add: cube
+//   right:
+//     add: sphere
+//     transform:
+//       - [ scale, 1.3, 1.3, 1.3 ]
+fn csg_from_node(node: &yaml::Hash, defs: &Definitions) -> Result<Object, SceneError> {
+    let operation = match str_from_key(node, "operation")? {
+        Some("union") => CsgOperation::Union,
+        Some("intersection") => CsgOperation::Intersection,
+        Some("difference") => CsgOperation::Difference,
+        Some(op) => return Err(SceneError::unknown("operation", op)),
+        None => return Err(SceneError::missing("operation")),
+    };
+    let operand = |key: &str| {
+        let operand_node = node
+            .get(&Yaml::from_str(key))
+            .ok_or_else(|| SceneError::missing(key))?;
+        child_from_node(operand_node, key, defs).map_err(|err| err.inside(key))
     };
-    object.material = material_from_node(defs, node);
-    object.transformation = transform_from_node(defs, node).unwrap();
+    Ok(Csg::new(operation, operand("left")?, operand("right")?))
+}
 
-    object
+/// object nested inside another one (like a group or csg). Can also be the name of a definition.
+/// - key: where the child is in its parent
+fn child_from_node(child: &Yaml, key: &str, defs: &Definitions) -> Result<Object, SceneError> {
+    let child = find_hash_in_definitions(defs, child, key)?;
+    let type_name = str_from_key(child, "add")?.ok_or_else(|| SceneError::missing("add"))?;
+    obj_from_node(child, type_name, defs)
+}
+
+// - add: obj
+//   file: models/teapot.obj
+//   transform:
+//     - [ scale, 0.1, 0.1, 0.1 ]
+//   material:
+//     color: [ 1, 0.3, 0.2 ]
+fn obj_file_from_node(node: &yaml::Hash, material: &Material) -> Result<Object, SceneError> {
+    let path = str_from_key(node, "file")?.ok_or_else(|| SceneError::missing("file"))?;
+    // the obj errors know their line in the obj file, the position is the 'file:' key in the yaml
+    let io_error = |info: String| {
+        SceneError::new(SceneErrorKind::Io(format!(
+            "unable to read obj file '{}': {}",
+            path, info
+        )))
+        .with_key("file")
+    };
+    let obj_str = std::fs::read_to_string(path).map_err(|err| io_error(err.to_string()))?;
+    let parser = ObjParser::new_from_str(&obj_str).map_err(|err| io_error(err.to_string()))?;
+    Ok(parser.into_group(material))
 }
 
 // ...
@@ -80,46 +229,68 @@ fn obj_from_node(node: &yaml::Hash, type_name: &str, defs: &Definitions) -> Obje
 // transform:
 // - large-obj                  #-definined-above
 // - [ translate, 0, 0, 500 ]   # make changes to large-obj
-fn transform_from_node(defs: &Definitions, node: &yaml::Hash) -> Option<Matrix> {
+fn transform_from_node(defs: &Definitions, node: &yaml::Hash) -> Result<Matrix, SceneError> {
     let mut matrix = Matrix::default(); // identity matrix that doesn't transforms
 
     // we check for transformations if we find the keyword. We expect them to be an array.
     if let Some(transform_node) = node.get(&Yaml::from_str("transform")) {
         let transforms_raw = transform_node
             .as_vec()
-            .expect("expect 'transform:' to contain an array.");
+            .ok_or_else(|| SceneError::invalid("transform", "a list of transformations"))?;
         let mut transforms_resolved = vec![];
-        recursive_resolve_transforms(defs, transforms_raw, &mut transforms_resolved);
+        recursive_resolve_transforms(defs, transforms_raw, &mut transforms_resolved)?;
 
         for one_transform in transforms_resolved {
-            let arr = array_from_yaml(defs, &one_transform);
+            let arr = array_from_yaml(defs, &one_transform, "transform")?;
 
             // expect identifier [0] that describes 'transformation-type'
-            let identifier = arr[0]
-                .as_str()
-                .expect("expect each transformation to start with a type.");
+            let identifier = arr
+                .first()
+                .and_then(|identifier| identifier.as_str())
+                .ok_or_else(|| {
+                    SceneError::invalid("transform", "each transformation to start with a type")
+                })?;
 
             //expect next numbers to be the 'args'
+            let args = |count: usize| -> Result<Vec<f64>, SceneError> {
+                let args: Option<Vec<f64>> = arr[1..].iter().map(as_f64).collect();
+                match args {
+                    Some(args) if args.len() == count => Ok(args),
+                    _ => Err(SceneError::invalid(
+                        "transform",
+                        &format!("{} numbers after '{}'", count, identifier),
+                    )),
+                }
+            };
             matrix = match identifier {
-                "rotate-x" => matrix.rotate_x(as_f64(&arr[1])?),
-                "rotate-y" => matrix.rotate_y(as_f64(&arr[1])?),
-                "rotate-z" => matrix.rotate_z(as_f64(&arr[1])?),
-                "translate" => matrix.translate(as_f64(&arr[1])?, as_f64(&arr[2])?, as_f64(&arr[3])?),
-                "scale" => matrix.scale(as_f64(&arr[1])?, as_f64(&arr[2])?, as_f64(&arr[3])?),
-                "shear" => matrix.shear(
-                    as_f64(&arr[1])?,
-                    as_f64(&arr[2])?,
-                    as_f64(&arr[3])?,
-                    as_f64(&arr[4])?,
-                    as_f64(&arr[5])?,
-                    as_f64(&arr[6])?,
-                ),
-                unexp => panic!("Unexpected transformation identifier : {:?}, ", unexp),
+                "rotate-x" => matrix.rotate_x(args(1)?[0]),
+                "rotate-y" => matrix.rotate_y(args(1)?[0]),
+                "rotate-z" => matrix.rotate_z(args(1)?[0]),
+                "translate" => {
+                    let a = args(3)?;
+                    matrix.translate(a[0], a[1], a[2])
+                }
+                "scale" => {
+                    let a = args(3)?;
+                    matrix.scale(a[0], a[1], a[2])
+                }
+                "shear" => {
+                    let a = args(6)?;
+                    matrix.shear(a[0], a[1], a[2], a[3], a[4], a[5])
+                }
+                unexp => return Err(SceneError::unknown("transform", unexp)),
             };
         }
     }
 
-    return Some(matrix);
+    // everything that gets transformed needs the inverse
+    if !matrix.is_invertible() {
+        return Err(SceneError::invalid(
+            "transform",
+            "transformations that can be undone (no scaling by 0)",
+        ));
+    }
+    Ok(matrix)
 }
 
 /// push resolved transformations into transformations vec. Recurses trough definitions.
@@ -127,84 +298,430 @@ fn recursive_resolve_transforms(
     defs: &Definitions,
     array: &[Yaml],
     transformations: &mut Vec<Yaml>,
-) {
+) -> Result<(), SceneError> {
     for transform in array {
         match transform[0].as_str() {
             Some(_) => transformations.push(transform.clone()),
             None => {
-                let embedded_transformations = array_from_yaml(defs, transform);
-                recursive_resolve_transforms(defs, embedded_transformations, transformations);
+                let embedded_transformations = array_from_yaml(defs, transform, "transform")?;
+                recursive_resolve_transforms(defs, embedded_transformations, transformations)?;
             }
         }
     }
+    Ok(())
 }
 
-fn array_from_yaml<'a>(definitions: &'a Definitions, yaml: &'a Yaml) -> &'a yaml::Array {
+fn array_from_yaml<'a>(
+    definitions: &'a Definitions,
+    yaml: &'a Yaml,
+    key: &str,
+) -> Result<&'a yaml::Array, SceneError> {
     match yaml.as_vec() {
-        Some(hash) => hash,
-        None => definitions
-            .get(yaml)
-            .unwrap_or_else(|| panic!("Definition {:?} not found", yaml))
+        Some(hash) => Ok(hash),
+        None => find_definition(definitions, yaml, key)?
             .as_vec()
-            .unwrap(),
+            .ok_or_else(|| SceneError::invalid(key, "the definition to be a list")),
     }
 }
 
-fn material_from_node(defs: &Definitions, node: &yaml::Hash) -> Material {
+fn material_from_node(defs: &Definitions, node: &yaml::Hash) -> Result<Material, SceneError> {
     let mut default = Material::default();
     match node.get(&Yaml::from_str("material")) {
         Some(material_node) => {
-            let mat_hash = &find_hash_in_definitions(defs, material_node);
-            if let Some(val) = f64_from_key(mat_hash, "ambient") {
+            let mat_hash = find_hash_in_definitions(defs, material_node, "material")?;
+            if let Some(val) = f64_from_key(mat_hash, "ambient")? {
                 default.ambient = val;
             }
-            if let Some(val) = f64_from_key(mat_hash, "diffuse") {
+            if let Some(val) = f64_from_key(mat_hash, "diffuse")? {
                 default.diffuse = val;
             }
-            if let Some(val) = f64_from_key(mat_hash, "reflective") {
+            if let Some(val) = f64_from_key(mat_hash, "reflective")? {
                 default.reflective = val;
             }
-            if let Some(val) = f64_from_key(mat_hash, "refractive-index") {
+            if let Some(val) = f64_from_key(mat_hash, "refractive-index")? {
                 default.refractive_index = val;
             }
-            if let Some(val) = f64_from_key(mat_hash, "shininess") {
+            if let Some(val) = f64_from_key(mat_hash, "shininess")? {
                 default.shininess = val;
             }
-            if let Some(val) = f64_from_key(mat_hash, "specular") {
+            if let Some(val) = f64_from_key(mat_hash, "specular")? {
                 default.specular = val;
             }
-            if let Some(val) = f64_from_key(mat_hash, "transparency") {
+            if let Some(val) = f64_from_key(mat_hash, "transparency")? {
                 default.transparency = val;
             }
-            if let Some(val) = color_from_key(&mat_hash, "color") {
+            if let Some(val) = color_from_key(mat_hash, "color")? {
                 default.color(val);
             }
-            // TODO: extend for pattern if i care to continute.
+            if let Some(pattern_node) = mat_hash.get(&Yaml::from_str("pattern")) {
+                let pattern_hash = find_hash_in_definitions(defs, pattern_node, "pattern")?;
+                default.pattern =
+                    pattern_from_node(defs, pattern_hash).map_err(|err| err.inside("pattern"))?;
+            }
 
-            default
+            Ok(default)
         }
-        None => default,
+        None => Ok(default),
     }
 }
 
-fn light_from_node(node: &yaml::Hash) -> Light {
-    Light::new_point_light(
-        point_from_key(node, "at").unwrap_or(Point::new(-6., 6., -10.)),
-        color_from_key(node, "intensity").unwrap_or(WHITE),
-    )
+// - add: light
+//   type: area                 # point (default) | area
+//   corner: [ -1, 2, 4 ]
+//   uvec: [ 2, 0, 0 ]
+//   vvec: [ 0, 2, 0 ]
+//   usteps: 10
+//   vsteps: 10
+//   jitter: true
+//   intensity: [ 1.5, 1.5, 1.5 ]
+//
+// - add: light
+//   type: spot                 # full intensity inside inner-angle, fades out till outer-angle (radians)
+//   at: [ 0, 10, 0 ]
+//   direction: [ 0, -1, 0 ]
+//   inner-angle: 0.3
+//   outer-angle: 0.5
+//
+// - add: light
+//   type: directional          # or sun. Infinitely far away, so only the direction matters
+//   direction: [ 1, -1, 1 ]
+//
+// - add: light
+//   at: [ 0, 3, 0 ]
+//   intensity: [ 20, 20, 20 ]  # attenuated lights can be brighter than 1
+//   attenuation: inverse-square # none (default) | inverse-square | [ constant, linear, quadratic ]
+// material:
+//   pattern:                   # replaces the color. Can also be the name of a definition
+//     type: checkers           # stripes | gradient | rings | checkers | blend | marble | wood | clouds
+//     colors:
+//       - [ 1, 1, 1 ]
+//       - type: stripes        # a color or a pattern (or the name of a definition of one)
+//         colors: [ [ 1, 0, 0 ], [ 0, 0, 1 ] ]
+//         transform:           # on top of the transform of the parent pattern
+//           - [ scale, 0.2, 0.2, 0.2 ]
+//     transform:               # on top of the transform of the object
+//       - [ scale, 0.5, 0.5, 0.5 ]
+//
+//   pattern:
+//     type: perturb            # moves the points around a bit with noise
+//     scale: 0.2               # how far at most. Default: 0.2
+//     pattern:                 # the pattern that gets perturbed
+//       type: stripes
+//       colors: [ [ 1, 1, 1 ], [ 0, 0, 0 ] ]
+//
+//   pattern:
+//     type: marble             # marble | wood | clouds. Noise, so no two are the same
+//     colors: [ [ 0.9, 0.9, 0.85 ], [ 0.3, 0.3, 0.35 ] ]
+//     octaves: 4               # more adds finer detail. Default: 4
+//     frequency: 1             # higher makes the noise smaller. Default: 1
+//     seed: 7                  # another seed gives other noise. Default: 0
+//
+//   pattern:
+//     type: map                # an image wrapped around the object
+//     mapping: spherical       # spherical | planar | cylindrical | cube (an unfolded cube, 4 x 3 faces)
+//     file: earth.ppm          # ppm or png. Relative to where the renderer runs
+//     filter: bilinear         # bilinear (default) | nearest
+fn pattern_from_node(defs: &Definitions, node: &yaml::Hash) -> Result<Pattern, SceneError> {
+    let type_name = required(str_from_key(node, "type")?, "type")?;
+    let pattern = match type_name {
+        "perturb" => {
+            let inner = node
+                .get(&Yaml::from_str("pattern"))
+                .ok_or_else(|| SceneError::missing("pattern"))?;
+            let inner = pattern_from_node(defs, find_hash_in_definitions(defs, inner, "pattern")?)
+                .map_err(|err| err.inside("pattern"))?;
+            Pattern::new_perturb(inner, f64_from_key(node, "scale")?.unwrap_or(0.2))
+        }
+        "map" => {
+            let mapping = match required(str_from_key(node, "mapping")?, "mapping")? {
+                "spherical" => UvMapping::Spherical,
+                "planar" => UvMapping::Planar,
+                "cylindrical" => UvMapping::Cylindrical,
+                "cube" => UvMapping::Cube,
+                unexp => return Err(SceneError::unknown("mapping", unexp)),
+            };
+            let filter = match str_from_key(node, "filter")? {
+                None => Filter::default(),
+                Some("bilinear") => Filter::Bilinear,
+                Some("nearest") => Filter::Nearest,
+                Some(unexp) => return Err(SceneError::unknown("filter", unexp)),
+            };
+            let path = required(str_from_key(node, "file")?, "file")?;
+            Pattern::new_map(mapping, UvImage::new(image_from_file(path)?, filter))
+        }
+        _ => {
+            let colors = node
+                .get(&Yaml::from_str("colors"))
+                .ok_or_else(|| SceneError::missing("colors"))?;
+            let (a, b) = match colors.as_vec().map(|colors| colors.as_slice()) {
+                Some([a, b]) => (
+                    sub_pattern_from_yaml(defs, a)
+                        .map_err(|err| err.inside("[0]").inside("colors"))?,
+                    sub_pattern_from_yaml(defs, b)
+                        .map_err(|err| err.inside("[1]").inside("colors"))?,
+                ),
+                _ => {
+                    return Err(SceneError::invalid(
+                        "colors",
+                        "a list of 2 colors or patterns",
+                    ))
+                }
+            };
+            match type_name {
+                "stripes" => Pattern::new_stripe(a, b),
+                "gradient" => Pattern::new_gradient(a, b),
+                "rings" => Pattern::new_ring(a, b),
+                "checkers" => Pattern::new_checkers(a, b),
+                "blend" => Pattern::new_blend(a, b),
+                "marble" => Pattern::new_marble(a, b, fractal_from_node(node)?),
+                "wood" => Pattern::new_wood(a, b, fractal_from_node(node)?),
+                "clouds" => Pattern::new_clouds(a, b, fractal_from_node(node)?),
+                typ => return Err(SceneError::unknown("type", typ)),
+            }
+        }
+    };
+    match node.contains_key(&Yaml::from_str("transform")) {
+        true => Ok(pattern.with_pattern_transform(transform_from_node(defs, node)?)),
+        false => Ok(pattern),
+    }
+}
+
+/// one of the 'colors' of a pattern: [ r, g, b ], a pattern or the name of a defined pattern
+fn sub_pattern_from_yaml(defs: &Definitions, value: &Yaml) -> Result<Pattern, SceneError> {
+    match value.as_vec() {
+        Some(_) => Ok(color_from_yaml(value, "colors")?.into()),
+        None => pattern_from_node(defs, find_hash_in_definitions(defs, value, "colors")?),
+    }
+}
+
+/// reads a ppm or png (if the 'png' feature is on) file
+fn image_from_file(path: &str) -> Result<Canvas, SceneError> {
+    let io_error = |info: String| {
+        SceneError::new(SceneErrorKind::Io(format!(
+            "unable to read image file '{}': {}",
+            path, info
+        )))
+        .with_key("file")
+    };
+    let bytes = std::fs::read(path).map_err(|err| io_error(err.to_string()))?;
+    if bytes.starts_with(b"\x89PNG") {
+        canvas_from_png(&bytes).map_err(io_error)
+    } else {
+        Canvas::from_ppm(&bytes).map_err(io_error)
+    }
+}
+
+#[cfg(feature = "png")]
+fn canvas_from_png(bytes: &[u8]) -> Result<Canvas, String> {
+    let image = image::load_from_memory_with_format(bytes, image::ImageFormat::Png)
+        .map_err(|err| err.to_string())?
+        .to_rgb32f();
+    let mut canvas = Canvas::new(image.width() as usize, image.height() as usize);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let [r, g, b] = pixel.0;
+        canvas.write_px(
+            x as usize,
+            y as usize,
+            Col::new(r as f64, g as f64, b as f64),
+        );
+    }
+    Ok(canvas)
+}
+
+#[cfg(not(feature = "png"))]
+fn canvas_from_png(_bytes: &[u8]) -> Result<Canvas, String> {
+    Err("png support is turned off (the 'png' feature), use a ppm file instead".to_string())
+}
+
+fn fractal_from_node(node: &yaml::Hash) -> Result<Fractal, SceneError> {
+    let default = Fractal::default();
+    let octaves = match usize_from_key(node, "octaves")? {
+        Some(octaves) if octaves > 16 => {
+            return Err(SceneError::invalid("octaves", "at most 16 octaves"))
+        }
+        Some(octaves) => octaves as u32,
+        None => default.octaves,
+    };
+    let frequency = f64_from_key(node, "frequency")?.unwrap_or(default.frequency);
+    let seed = usize_from_key(node, "seed")?.map_or(default.noise.seed(), |seed| seed as u64);
+    Ok(Fractal::new(seed, octaves, frequency))
+}
+
+fn light_from_node(node: &yaml::Hash) -> Result<Light, SceneError> {
+    let intensity = intensity_from_key(node, "intensity")?.unwrap_or(WHITE);
+    let light = match str_from_key(node, "type")? {
+        None | Some("point") => Light::new_point_light(
+            point_from_key(node, "at")?.unwrap_or(Point::new(-6., 6., -10.)),
+            intensity,
+        ),
+        Some("area") => Light::new_area_light(
+            required(point_from_key(node, "corner")?, "corner")?,
+            required(vector_from_key(node, "uvec")?, "uvec")?,
+            usize_from_key(node, "usteps")?.unwrap_or(4).max(1),
+            required(vector_from_key(node, "vvec")?, "vvec")?,
+            usize_from_key(node, "vsteps")?.unwrap_or(4).max(1),
+            intensity,
+            bool_from_key(node, "jitter")?.unwrap_or(false),
+        ),
+        Some("spot") => {
+            let outer_angle =
+                f64_from_key(node, "outer-angle")?.unwrap_or(std::f64::consts::PI / 6.);
+            Light::new_spot_light(
+                required(point_from_key(node, "at")?, "at")?,
+                required(vector_from_key(node, "direction")?, "direction")?,
+                f64_from_key(node, "inner-angle")?
+                    .unwrap_or(outer_angle * 0.75)
+                    .min(outer_angle),
+                outer_angle,
+                intensity,
+            )
+        }
+        Some("directional") | Some("sun") => Light::new_directional_light(
+            required(vector_from_key(node, "direction")?, "direction")?,
+            intensity,
+        ),
+        Some(typ) => return Err(SceneError::unknown("type", typ)),
+    };
+    match attenuation_from_key(node, "attenuation")? {
+        Some(attenuation) => Ok(light.with_attenuation(attenuation)),
+        None => Ok(light),
+    }
+}
+
+fn attenuation_from_key(node: &yaml::Hash, key: &str) -> Result<Option<Attenuation>, SceneError> {
+    let Some(value) = node.get(&Yaml::from_str(key)) else {
+        return Ok(None);
+    };
+    match value.as_str() {
+        Some("none") => return Ok(Some(Attenuation::None)),
+        Some("inverse-square") => return Ok(Some(Attenuation::InverseSquare)),
+        Some(other) => return Err(SceneError::unknown(key, other)),
+        None => (),
+    }
+    let (constant, linear, quadratic) = three_f64(node, key)
+        .map_err(|_| SceneError::invalid(key, "a name or [ constant, linear, quadratic ]"))?
+        .unwrap_or_default();
+    // else the light would get stronger with distance or be divided by 0
+    if constant < 0. || linear < 0. || quadratic < 0. || constant + linear + quadratic == 0. {
+        return Err(SceneError::invalid(
+            key,
+            "[ constant, linear, quadratic ] that are not negative and not all 0",
+        ));
+    }
+    Ok(Some(Attenuation::Polynomial {
+        constant,
+        linear,
+        quadratic,
+    }))
+}
+
+// - add: settings
+//   max-depth: 5               # how often rays can get reflected/refracted in a row. Defaults to 5
+//   min-weight: 0.001          # rays contributing less than this to the pixel get dropped
+//   tile-size: 16              # the image gets rendered in tiles of 16x16 pixels
+//   passes: 8                  # progressive: renders the image 8 times and averages them. Defaults to 1
+//   samples: 16                # the sampling keys (see camera) can be set here too
+fn settings_from_node(node: &yaml::Hash, settings: &mut RenderSettings) -> Result<(), SceneError> {
+    if let Some(depth) = usize_from_key(node, "max-depth")? {
+        settings.max_depth = depth.min(u8::MAX as usize) as u8;
+    }
+    if let Some(min_weight) = f64_from_key(node, "min-weight")? {
+        settings.min_weight = min_weight;
+    }
+    if let Some(tile_size) = usize_from_key(node, "tile-size")? {
+        settings.tile_size = tile_size.max(1);
+    }
+    if let Some(passes) = usize_from_key(node, "passes")? {
+        settings.passes = passes.max(1);
+    }
+    sampling_from_node(node, settings)
 }
 
-fn camera_from_node(node: &yaml::Hash) -> Camera {
-    Camera::new(
-        usize_from_key(node, "width").unwrap_or(200),
-        usize_from_key(node, "height").unwrap_or(100),
-        f64_from_key(node, "field-of-view").unwrap_or(std::f64::consts::PI / 4.),
+// anti-aliasing, usually set in the camera node:
+//   samples: 16                # rays per pixel. Defaults to 1
+//   sampling: jittered         # jittered (default) | stratified | adaptive
+//   adaptive-threshold: 0.1    # adaptive only: how different the pixel corners can be before we refine
+//   adaptive-depth: 3          # adaptive only: how often a pixel can be split into 4
+fn sampling_from_node(node: &yaml::Hash, settings: &mut RenderSettings) -> Result<(), SceneError> {
+    if let Some(samples) = usize_from_key(node, "samples")? {
+        settings.samples = samples.max(1);
+    }
+    match str_from_key(node, "sampling")? {
+        None => (),
+        Some("jittered") => settings.sample_mode = SampleMode::Jittered,
+        Some("stratified") => settings.sample_mode = SampleMode::Stratified,
+        Some("adaptive") => {
+            settings.sample_mode = SampleMode::Adaptive {
+                threshold: f64_from_key(node, "adaptive-threshold")?.unwrap_or(0.1),
+                max_depth: usize_from_key(node, "adaptive-depth")?.unwrap_or(3).min(8) as u8,
+            }
+        }
+        Some(mode) => return Err(SceneError::unknown("sampling", mode)),
+    }
+    Ok(())
+}
+
+// - add: camera
+//   width: 400
+//   height: 200
+//   field-of-view: 0.785
+//   from: [ -6, 6, -10 ]
+//   to: [ 6, 0, 6 ]
+//   up: [ -0.45, 1, 0 ]
+//   samples: 16                # anti-aliasing, see sampling_from_node
+//   aperture: 0.2              # lens diameter for depth of field. Defaults to 0 (everything sharp)
+//   focal-distance: 12         # distance to the plane in focus. Defaults to the distance from -> to
+//   projection: perspective    # perspective (default) | orthographic | equirectangular | fisheye
+//   view-width: 10             # orthographic only: world units across the image width
+fn camera_from_node(node: &yaml::Hash) -> Result<Camera, SceneError> {
+    let from = point_from_key(node, "from")?.unwrap_or(Point::new(-6., 6., -10.));
+    let to = point_from_key(node, "to")?.unwrap_or(Point::new(6., 0., 6.));
+    if (to - from).magnitude().apx_eq(&0.) {
+        return Err(SceneError::invalid("to", "a different point than 'from'"));
+    }
+    let field_of_view = f64_from_key(node, "field-of-view")?.unwrap_or(std::f64::consts::PI / 4.);
+    let projection = match str_from_key(node, "projection")? {
+        None | Some("perspective") => Projection::Perspective,
+        Some("orthographic") => Projection::Orthographic {
+            // by default the same width a perspective camera sees at the 'to' point
+            view_width: match f64_from_key(node, "view-width")? {
+                Some(width) if width <= 0. => {
+                    return Err(SceneError::invalid("view-width", "a positive number"))
+                }
+                Some(width) => width,
+                None => 2. * (field_of_view / 2.).tan() * (to - from).magnitude(),
+            },
+        },
+        Some("equirectangular") => Projection::Equirectangular,
+        Some("fisheye") => Projection::Fisheye,
+        Some(projection) => return Err(SceneError::unknown("projection", projection)),
+    };
+    let size = |key: &str, default: usize| match usize_from_key(node, key)? {
+        Some(0) => Err(SceneError::invalid(key, "at least 1 pixel")),
+        size => Ok(size.unwrap_or(default)),
+    };
+    let aperture = f64_from_key(node, "aperture")?.unwrap_or(0.);
+    if aperture < 0. {
+        return Err(SceneError::invalid("aperture", "0 or a positive number"));
+    }
+    let focal_distance = f64_from_key(node, "focal-distance")?.unwrap_or((to - from).magnitude());
+    if focal_distance <= 0. {
+        return Err(SceneError::invalid("focal-distance", "a positive number"));
+    }
+    let up = vector_from_key(node, "up")?.unwrap_or(Vector::new(-0.45, 1., 0.));
+    let view = Matrix::view_transform_new(from, to, up);
+    if !view.is_invertible() {
+        return Err(SceneError::invalid(
+            "up",
+            "a vector that does not point along from -> to",
+        ));
+    }
+    Ok(
+        Camera::new(size("width", 200)?, size("height", 100)?, field_of_view)
+            .with_transform(view)
+            .with_depth_of_field(aperture, focal_distance)
+            .with_projection(projection),
     )
-    .with_transform(Matrix::view_transform_new(
-        point_from_key(node, "from").unwrap_or(Point::new(-6., 6., -10.)),
-        point_from_key(node, "to").unwrap_or(Point::new(6., 0., 6.)),
-        vector_from_key(node, "up").unwrap_or(Vector::new(-0.45, 1., 0.)),
-    ))
 }
 
 /// maps both float and int to -> f64
@@ -218,52 +735,125 @@ fn as_f64(yaml: &Yaml) -> Option<f64> {
     None
 }
 
-fn three_f64(node: &yaml::Hash, key: &str) -> Option<(f64, f64, f64)> {
-    let xyz = node.get(&Yaml::from_str(key))?.as_vec()?;
-    assert!(xyz.len() == 3, "Chooridantes must always have 3 values.");
-    Some((as_f64(&xyz[0])?, as_f64(&xyz[1])?, as_f64(&xyz[2])?))
+/// turns an optional value into an error if it is missing
+fn required<T>(value: Option<T>, key: &str) -> Result<T, SceneError> {
+    value.ok_or_else(|| SceneError::missing(key))
+}
+
+// all the *_from_key functions below return Ok(None) if the key is missing.
+// But if it is there, the value has to make sense.
+
+fn three_f64(node: &yaml::Hash, key: &str) -> Result<Option<(f64, f64, f64)>, SceneError> {
+    match node.get(&Yaml::from_str(key)) {
+        None => Ok(None),
+        Some(value) => three_f64_from_yaml(value, key).map(Some),
+    }
+}
+
+/// - key: where the value is, for the error
+fn three_f64_from_yaml(value: &Yaml, key: &str) -> Result<(f64, f64, f64), SceneError> {
+    let xyz: Option<Vec<f64>> = value
+        .as_vec()
+        .and_then(|xyz| xyz.iter().map(as_f64).collect());
+    match xyz.as_deref() {
+        Some(&[x, y, z]) => Ok((x, y, z)),
+        _ => Err(SceneError::invalid(key, "3 numbers like [ 1, 0.5, 0 ]")),
+    }
+}
+
+fn color_from_key(node: &yaml::Hash, key: &str) -> Result<Option<Col>, SceneError> {
+    match node.get(&Yaml::from_str(key)) {
+        None => Ok(None),
+        Some(value) => color_from_yaml(value, key).map(Some),
+    }
+}
+
+fn color_from_yaml(value: &Yaml, key: &str) -> Result<Col, SceneError> {
+    let (r, g, b) = three_f64_from_yaml(value, key)?;
+    if ![r, g, b].iter().all(|c| (0. ..=1.).contains(c)) {
+        return Err(SceneError::invalid(
+            key,
+            "a color with values between 0 and 1",
+        ));
+    }
+    Ok(Col::new(r, g, b))
 }
 
-fn color_from_key(node: &yaml::Hash, key: &str) -> Option<Col> {
-    let xyz = three_f64(node, key)?;
-    assert!(xyz.0 >= 0. && xyz.0 <= 1.);
-    assert!(xyz.1 >= 0. && xyz.1 <= 1.);
-    assert!(xyz.2 >= 0. && xyz.2 <= 1.);
-    Some(Col::new(xyz.0, xyz.1, xyz.2))
+/// like a color, but light can be brighter than 1
+fn intensity_from_key(node: &yaml::Hash, key: &str) -> Result<Option<Col>, SceneError> {
+    let Some((r, g, b)) = three_f64(node, key)? else {
+        return Ok(None);
+    };
+    if [r, g, b].iter().any(|c| *c < 0.) {
+        return Err(SceneError::invalid(
+            key,
+            "an intensity without negative values",
+        ));
+    }
+    Ok(Some(Col::new(r, g, b)))
 }
 
-fn point_from_key(node: &yaml::Hash, key: &str) -> Option<Point> {
-    let xyz = three_f64(node, key)?;
-    Some(Point::new(xyz.0, xyz.1, xyz.2))
+fn point_from_key(node: &yaml::Hash, key: &str) -> Result<Option<Point>, SceneError> {
+    Ok(three_f64(node, key)?.map(|(x, y, z)| Point::new(x, y, z)))
+}
+
+fn vector_from_key(node: &yaml::Hash, key: &str) -> Result<Option<Vector>, SceneError> {
+    Ok(three_f64(node, key)?.map(|(x, y, z)| Vector::new(x, y, z)))
+}
+
+fn f64_from_key(node: &yaml::Hash, key: &str) -> Result<Option<f64>, SceneError> {
+    match node.get(&Yaml::from_str(key)) {
+        None => Ok(None),
+        Some(value) => as_f64(value)
+            .map(Some)
+            .ok_or_else(|| SceneError::invalid(key, "a number")),
+    }
 }
 
-fn vector_from_key(node: &yaml::Hash, key: &str) -> Option<Vector> {
-    let xyz = three_f64(node, key)?;
-    Some(Vector::new(xyz.0, xyz.1, xyz.2))
+fn bool_from_key(node: &yaml::Hash, key: &str) -> Result<Option<bool>, SceneError> {
+    match node.get(&Yaml::from_str(key)) {
+        None => Ok(None),
+        Some(value) => value
+            .as_bool()
+            .map(Some)
+            .ok_or_else(|| SceneError::invalid(key, "true or false")),
+    }
 }
 
-fn f64_from_key(node: &yaml::Hash, key: &str) -> Option<f64> {
-    as_f64(node.get(&Yaml::from_str(key))?)
+fn usize_from_key(node: &yaml::Hash, key: &str) -> Result<Option<usize>, SceneError> {
+    match node.get(&Yaml::from_str(key)) {
+        None => Ok(None),
+        Some(value) => match value.as_i64() {
+            Some(int) if int >= 0 => Ok(Some(int as usize)),
+            _ => Err(SceneError::invalid(key, "a positive whole number")),
+        },
+    }
 }
 
-fn usize_from_key(node: &yaml::Hash, key: &str) -> Option<usize> {
-    let int = node.get(&Yaml::from_str(key))?.as_i64()?;
-    if int < 0 {
-        return None;
+fn str_from_key<'a>(node: &'a yaml::Hash, key: &str) -> Result<Option<&'a str>, SceneError> {
+    match node.get(&Yaml::from_str(key)) {
+        None => Ok(None),
+        Some(value) => value
+            .as_str()
+            .map(Some)
+            .ok_or_else(|| SceneError::invalid(key, "a name")),
     }
-    Some(int as usize)
 }
 
 type Definitions<'a> = HashMap<&'a Yaml, Yaml>;
 
-fn parse_definitions(yaml_list: &Vec<Yaml>) -> Definitions {
+fn parse_definitions<'a>(
+    yaml_list: &[&'a yaml::Hash],
+    positions: &Positions,
+) -> Result<Definitions<'a>, SceneError> {
     let mut definitions = HashMap::new();
 
-    for node in yaml_list.iter().map(|yaml| yaml.as_hash().unwrap()) {
+    for (index, node) in yaml_list.iter().enumerate() {
+        let in_entry = |err: SceneError| err.in_entry(index, node, positions);
         if let Some(define_node) = node.get(&Yaml::from_str("define")) {
             let this_values = node
                 .get(&Yaml::from_str("value"))
-                .expect("Could not find values in Definition.");
+                .ok_or_else(|| in_entry(SceneError::missing("value")))?;
 
             // if we find 'extend' we combine parent & self (only 1 level of inheritance possible)
             let combined_values = match node.get(&Yaml::from_str("extend")) {
@@ -271,37 +861,424 @@ fn parse_definitions(yaml_list: &Vec<Yaml>) -> Definitions {
                 None => this_values.clone(),
                 // combine with inherited parent-values
                 Some(extend_id) => {
-                    if let Some(self_values_hash) = this_values.as_hash() {
-                        let mut parent_hash =
-                            find_hash_in_definitions(&definitions, extend_id).clone();
-                        parent_hash.extend(self_values_hash.clone().into_iter());
-                        Yaml::Hash(parent_hash)
-                    } else {
-                        unreachable!("Not implemented. ");
-                    }
+                    let self_values_hash = this_values.as_hash().ok_or_else(|| {
+                        in_entry(SceneError::invalid(
+                            "value",
+                            "a mapping to extend the definition with",
+                        ))
+                    })?;
+                    let mut parent_hash =
+                        find_hash_in_definitions(&definitions, extend_id, "extend")
+                            .map_err(in_entry)?
+                            .clone();
+                    parent_hash.extend(self_values_hash.clone());
+                    Yaml::Hash(parent_hash)
                 }
             };
             definitions.insert(define_node, combined_values);
         }
     }
-    definitions
+    Ok(definitions)
 }
 
 /// to search pre-defined 'variable-like' / 'global-variables' definitions with the 'define'-keyword
 /// these can be used while adding other elements/definitions to the scene.
-/// fn get_hash<'a>(definitions: &'a Definitions, yaml: &'a Yaml) -> &'a yaml::Hash {
-fn find_hash_in_definitions<'a>(defs: &'a Definitions, yaml_key: &'a Yaml) -> &'a yaml::Hash {
+/// - key: where the yaml got used, for the error
+fn find_hash_in_definitions<'a>(
+    defs: &'a Definitions,
+    yaml_key: &'a Yaml,
+    key: &str,
+) -> Result<&'a yaml::Hash, SceneError> {
     match yaml_key.as_hash() {
-        Some(hash) => hash,
-        None => defs
-            .get(yaml_key)
-            .unwrap_or_else(|| {
-                panic!(
-                    "Definition {:?} not found, must be declared before use.",
-                    yaml_key
-                )
-            })
+        Some(hash) => Ok(hash),
+        None => find_definition(defs, yaml_key, key)?
             .as_hash()
-            .unwrap(),
+            .ok_or_else(|| SceneError::invalid(key, "the definition to be a mapping")),
+    }
+}
+
+fn find_definition<'a>(
+    defs: &'a Definitions,
+    yaml_key: &Yaml,
+    key: &str,
+) -> Result<&'a Yaml, SceneError> {
+    defs.get(yaml_key).ok_or_else(|| {
+        let name = match yaml_key.as_str() {
+            Some(name) => name.to_string(),
+            None => format!("{:?}", yaml_key),
+        };
+        SceneError::new(SceneErrorKind::UnknownDefinition(name)).with_key(key)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIGHT_AND_CAMERA: &str = "
+- add: camera
+  width: 10
+  height: 10
+- add: light
+  at: [ 0, 5, 0 ]
+";
+
+    fn error_of(yaml_str: &str) -> SceneError {
+        match SceneToRun::new_from_yaml(yaml_str) {
+            Ok(_) => panic!("expected the scene to be broken"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn a_valid_scene_gets_parsed() {
+        let yaml_str = format!("{LIGHT_AND_CAMERA}- add: sphere\n");
+        let scene = SceneToRun::new_from_yaml(&yaml_str).unwrap();
+        assert_eq!(scene.camera.width, 10);
+        assert_eq!(scene.world.lights.len(), 1);
+        assert_eq!(scene.world.objects.len(), 1);
+    }
+
+    #[test]
+    fn syntax_errors_know_where_they_are() {
+        let err = error_of("- add: camera\n  from: [ 1, 2\n  to: 3");
+        assert!(matches!(err.kind, SceneErrorKind::Syntax(_)));
+        assert_eq!(err.position.map(|(line, _)| line), Some(3));
+    }
+
+    #[test]
+    fn unknown_types_point_to_their_entry() {
+        let err = error_of(&format!("{LIGHT_AND_CAMERA}- add: teapot\n"));
+        assert_eq!(err.kind, SceneErrorKind::Unknown("teapot".to_string()));
+        assert_eq!(err.key.as_deref(), Some("add"));
+        assert_eq!(err.node.as_deref(), Some("add: teapot"));
+        assert_eq!(err.position, Some((7, 3)));
+        assert_eq!(
+            err.to_string(),
+            "line 7, column 3: unknown 'teapot' for 'add' (in 'add: teapot')"
+        );
+    }
+
+    #[test]
+    fn invalid_values_point_to_their_key() {
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: sphere\n  material:\n    color: [ 1, 2, 0 ]\n"
+        ));
+        assert!(matches!(err.kind, SceneErrorKind::InvalidValue(_)));
+        assert_eq!(err.key.as_deref(), Some("color"));
+        assert_eq!(err.node.as_deref(), Some("add: sphere"));
+        assert_eq!(err.position, Some((9, 5)));
+
+        let err = error_of(&format!("{LIGHT_AND_CAMERA}- add: light\n  type: spot\n"));
+        assert_eq!(err.kind, SceneErrorKind::MissingKey);
+        assert_eq!(err.key.as_deref(), Some("at"));
+        // no 'at:' to point to, so we point to the entry
+        assert_eq!(err.position, Some((7, 3)));
+    }
+
+    #[test]
+    fn values_the_renderer_can_not_use_are_invalid() {
+        let light = "- add: light\n  at: [ 0, 5, 0 ]\n";
+        for (camera, key) in [
+            ("aperture: -0.5", "aperture"),
+            ("focal-distance: 0", "focal-distance"),
+            ("projection: orthographic\n  view-width: -2", "view-width"),
+            ("from: [ 0, 1, 0 ]\n  to: [ 0, 1, 0 ]", "to"),
+            (
+                "from: [ 0, 1, 0 ]\n  to: [ 0, 5, 0 ]\n  up: [ 0, 1, 0 ]",
+                "up",
+            ),
+        ] {
+            let err = error_of(&format!("- add: camera\n  {camera}\n{light}"));
+            assert!(
+                matches!(err.kind, SceneErrorKind::InvalidValue(_)),
+                "{camera}"
+            );
+            assert_eq!(err.key.as_deref(), Some(key));
+            assert_eq!(err.node.as_deref(), Some("add: camera"));
+            assert!(err.position.is_some());
+        }
+
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: sphere\n  transform:\n    - [ scale, 0, 1, 1 ]\n"
+        ));
+        assert!(matches!(err.kind, SceneErrorKind::InvalidValue(_)));
+        assert_eq!(err.key.as_deref(), Some("transform"));
+        assert_eq!(err.position, Some((8, 3)));
+
+        // patterns have their own transform
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: sphere\n  material:\n    pattern:\n      type: stripes\n      colors: [ [ 1, 1, 1 ], [ 0, 0, 0 ] ]\n      transform:\n        - [ scale, 1, 0, 1 ]\n"
+        ));
+        assert_eq!(err.key.as_deref(), Some("transform"));
+        assert_eq!(err.position, Some((12, 7)));
+    }
+
+    #[test]
+    fn nested_errors_point_at_their_own_key() {
+        // the object has a transform too, but the broken one is the pattern's
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: sphere\n  transform:\n    - [ scale, 2, 2, 2 ]\n  material:\n    pattern:\n      type: stripes\n      colors: [ [ 1, 1, 1 ], [ 0, 0, 0 ] ]\n      transform:\n        - [ scale, 1, 0, 1 ]\n"
+        ));
+        assert_eq!(err.key.as_deref(), Some("transform"));
+        assert_eq!(err.path[..], ["material", "pattern"]);
+        assert_eq!(err.position, Some((14, 7)));
+
+        // children of a group
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: group\n  transform:\n    - [ translate, 0, 1, 0 ]\n  children:\n    - add: sphere\n    - add: cube\n      transform:\n        - [ scale, 0, 1, 1 ]\n"
+        ));
+        assert_eq!(err.position, Some((13, 7)));
+
+        // operands of a csg
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: csg\n  operation: union\n  transform:\n    - [ translate, 0, 1, 0 ]\n  left:\n    add: sphere\n  right:\n    add: cube\n    transform:\n      - [ scale, 1, 1, 0 ]\n"
+        ));
+        assert_eq!(err.position, Some((15, 5)));
+
+        // patterns nested in the colors of a pattern
+        let nested = |first_color: &str| {
+            format!(
+                "{LIGHT_AND_CAMERA}- add: plane\n  transform: [ [ translate, 0, 1, 0 ] ]\n  material:\n    pattern:\n      type: checkers\n      transform: [ [ scale, 2, 2, 2 ] ]\n      colors:\n        - {first_color}\n        - type: stripes\n          colors: [ [ 1, 0, 0 ], [ 0, 0, 1 ] ]\n          transform: [ [ scale, 0, 1, 1 ] ]\n"
+            )
+        };
+        let err = error_of(&nested("[ 1, 1, 1 ]"));
+        assert_eq!(err.path[..], ["material", "pattern", "colors", "[1]"]);
+        assert_eq!(err.position, Some((17, 11)));
+        // a broken color points at itself
+        let err = error_of(&nested("[ 1, 1 ]"));
+        assert_eq!(err.key.as_deref(), Some("colors"));
+        assert_eq!(err.position, Some((14, 11)));
+    }
+
+    #[test]
+    fn broken_obj_files_are_errors() {
+        let path = std::env::temp_dir().join("parselib_yaml_broken.obj");
+        std::fs::write(&path, "v 0 1 0\nv -1 0 0\nv 1 0 0\nf 1 2 7\n").unwrap();
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: obj\n  file: {}\n",
+            path.display()
+        ));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.key.as_deref(), Some("file"));
+        assert_eq!(err.node.as_deref(), Some("add: obj"));
+        assert_eq!(err.position, Some((8, 3)));
+        let SceneErrorKind::Io(info) = err.kind else {
+            panic!("expected an io error, got {:?}", err.kind);
+        };
+        assert!(
+            info.ends_with("line 4: face references unknown vertex '7'"),
+            "{info}"
+        );
+    }
+
+    #[test]
+    fn attenuation_has_to_divide_by_something() {
+        let camera = "- add: camera\n";
+        for terms in ["[ 0, 0, 0 ]", "[ 1, -0.5, 0 ]"] {
+            let err = error_of(&format!(
+                "{camera}- add: light\n  at: [ 0, 5, 0 ]\n  attenuation: {terms}\n"
+            ));
+            assert!(matches!(err.kind, SceneErrorKind::InvalidValue(_)));
+            assert_eq!(err.key.as_deref(), Some("attenuation"));
+            assert_eq!(err.position, Some((4, 3)));
+        }
+        let scene = SceneToRun::new_from_yaml(&format!(
+            "{camera}- add: light\n  at: [ 0, 5, 0 ]\n  attenuation: [ 0, 0, 1 ]\n"
+        ))
+        .unwrap();
+        assert_eq!(
+            scene.world.lights[0].attenuation(),
+            Attenuation::Polynomial {
+                constant: 0.,
+                linear: 0.,
+                quadratic: 1.
+            }
+        );
+    }
+
+    #[test]
+    fn definitions_have_to_exist() {
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: cube\n  material: blue\n"
+        ));
+        assert_eq!(
+            err.kind,
+            SceneErrorKind::UnknownDefinition("blue".to_string())
+        );
+        assert_eq!(err.position, Some((8, 3)));
+    }
+
+    #[test]
+    fn materials_can_have_patterns() {
+        let yaml_str = format!(
+            "{LIGHT_AND_CAMERA}
+- define: checkers
+  value:
+    type: checkers
+    colors: [ [ 1, 1, 1 ], [ 0, 0, 0 ] ]
+    transform:
+      - [ scale, 0.5, 0.5, 0.5 ]
+- add: plane
+  material:
+    pattern: checkers
+- add: sphere
+  material:
+    pattern:
+      type: stripes
+      colors:
+        - [ 1, 0, 0 ]
+        - [ 0, 0, 1 ]
+"
+        );
+        let scene = SceneToRun::new_from_yaml(&yaml_str).unwrap();
+        // everything ends up in the root group of the bvh
+        let patterns: Vec<&Pattern> = scene.world.objects[0]
+            .children()
+            .iter()
+            .map(|obj| &obj.material.pattern)
+            .collect();
+        assert!(patterns.contains(
+            &&Pattern::new_checkers(WHITE, Col::new(0., 0., 0.))
+                .with_pattern_transform(Matrix::scaling_new(0.5, 0.5, 0.5))
+        ));
+        assert!(patterns.contains(&&Pattern::new_stripe(
+            Col::new(1., 0., 0.),
+            Col::new(0., 0., 1.)
+        )));
+
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: plane\n  material:\n    pattern:\n      type: rings\n      colors: [ [ 1, 1, 1 ] ]\n"
+        ));
+        assert_eq!(err.key.as_deref(), Some("colors"));
+        assert_eq!(err.position, Some((11, 7)));
+    }
+
+    #[test]
+    fn patterns_can_be_nested() {
+        let yaml_str = format!(
+            "{LIGHT_AND_CAMERA}
+- define: stripes
+  value:
+    type: stripes
+    colors: [ [ 1, 0, 0 ], [ 0, 0, 1 ] ]
+- add: plane
+  material:
+    pattern:
+      type: checkers
+      colors:
+        - stripes
+        - type: blend
+          colors:
+            - [ 0, 0, 0 ]
+            - type: perturb
+              scale: 0.5
+              pattern: stripes
+          transform:
+            - [ scale, 0.5, 0.5, 0.5 ]
+"
+        );
+        let scene = SceneToRun::new_from_yaml(&yaml_str).unwrap();
+        let stripes = Pattern::new_stripe(Col::new(1., 0., 0.), Col::new(0., 0., 1.));
+        let blend = Pattern::new_blend(
+            Col::new(0., 0., 0.),
+            Pattern::new_perturb(stripes.clone(), 0.5),
+        )
+        .with_pattern_transform(Matrix::scaling_new(0.5, 0.5, 0.5));
+        assert_eq!(
+            scene.world.objects[0].children()[0].material.pattern,
+            Pattern::new_checkers(stripes, blend)
+        );
+
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: plane\n  material:\n    pattern:\n      type: perturb\n      scale: 0.5\n"
+        ));
+        assert_eq!(err.kind, SceneErrorKind::MissingKey);
+        assert_eq!(err.key.as_deref(), Some("pattern"));
+    }
+
+    #[test]
+    fn image_maps() {
+        let path = std::env::temp_dir().join("parselib_yaml_image_maps.ppm");
+        std::fs::write(&path, "P3\n2 1\n255\n255 0 0  0 0 255\n").unwrap();
+        let yaml_str = format!(
+            "{LIGHT_AND_CAMERA}
+- add: sphere
+  material:
+    pattern:
+      type: map
+      mapping: spherical
+      file: {}
+      filter: nearest
+",
+            path.display()
+        );
+        let scene = SceneToRun::new_from_yaml(&yaml_str).unwrap();
+        let mut canvas = Canvas::new(2, 1);
+        canvas
+            .write_px(0, 0, Col::new(1., 0., 0.))
+            .write_px(1, 0, Col::new(0., 0., 1.));
+        assert_eq!(
+            scene.world.objects[0].children()[0].material.pattern,
+            Pattern::new_map(UvMapping::Spherical, UvImage::new(canvas, Filter::Nearest))
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: cube\n  material:\n    pattern:\n      type: map\n      mapping: cube\n      file: not-there.ppm\n"
+        ));
+        assert!(matches!(err.kind, SceneErrorKind::Io(_)));
+        assert_eq!(err.position, Some((12, 7)));
+
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: cube\n  material:\n    pattern:\n      type: map\n      mapping: cubic\n      file: a.ppm\n"
+        ));
+        assert_eq!(err.kind, SceneErrorKind::Unknown("cubic".to_string()));
+    }
+
+    #[test]
+    fn noise_patterns() {
+        let yaml_str = format!(
+            "{LIGHT_AND_CAMERA}
+- add: plane
+  material:
+    pattern:
+      type: wood
+      colors: [ [ 1, 1, 1 ], [ 0, 0, 0 ] ]
+      octaves: 2
+      frequency: 3.5
+      seed: 42
+- add: sphere
+  material:
+    pattern:
+      type: clouds
+      colors: [ [ 1, 1, 1 ], [ 0, 0, 0 ] ]
+"
+        );
+        let scene = SceneToRun::new_from_yaml(&yaml_str).unwrap();
+        let patterns: Vec<&Pattern> = scene.world.objects[0]
+            .children()
+            .iter()
+            .map(|obj| &obj.material.pattern)
+            .collect();
+        let black = Col::new(0., 0., 0.);
+        assert!(patterns.contains(&&Pattern::new_wood(WHITE, black, Fractal::new(42, 2, 3.5))));
+        assert!(patterns.contains(&&Pattern::new_clouds(WHITE, black, Fractal::default())));
+
+        let err = error_of(&format!(
+            "{LIGHT_AND_CAMERA}- add: plane\n  material:\n    pattern:\n      type: marble\n      colors: [ [ 1, 1, 1 ], [ 0, 0, 0 ] ]\n      seed: -1\n"
+        ));
+        assert_eq!(err.key.as_deref(), Some("seed"));
+        assert_eq!(err.position, Some((12, 7)));
+    }
+
+    #[test]
+    fn scenes_need_a_camera_and_a_light() {
+        let err = error_of("- add: light\n");
+        assert_eq!(err.kind, SceneErrorKind::MissingCamera);
+        let err = error_of("- add: camera\n");
+        assert_eq!(err.kind, SceneErrorKind::MissingLight);
+        let err = error_of("add: camera\n");
+        assert!(matches!(err.kind, SceneErrorKind::Structure(_)));
     }
 }
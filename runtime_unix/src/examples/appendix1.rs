@@ -1,4 +1,4 @@
-use mathlib_renderer::io::ppm::write_to_file;
+use mathlib_renderer::{io::ppm::write_to_file, visual::canvas::Canvas};
 use parselib_yaml::yaml::SceneToRun;
 
 use crate::png_io::canvas_png_save;
@@ -23,16 +23,33 @@ pub fn parse_yaml_cli() {
     let yaml_str = std::fs::read_to_string(path).unwrap();
 
     // parse yaml to scene
-    let scene = SceneToRun::new_from_yaml(&yaml_str);
+    let scene = match SceneToRun::new_from_yaml(&yaml_str) {
+        Ok(scene) => scene,
+        Err(err) => {
+            eprintln!("unable to load the scene: {err}");
+            std::process::exit(1);
+        }
+    };
 
     // render scene to output .ppm and .png files in parallel
     run_scene(scene);
 }
 
 /// render the scene out and write the result to a file
+/// - with multiple progressive passes out.png gets updated after every pass
 pub fn run_scene(scene: SceneToRun) {
-
-    let canvas = crate::parallel::render_parallel(scene.camera, scene.world);
+    let passes = scene.settings.passes;
+    let write_pass = |pass: usize, canvas: &Canvas| {
+        if pass < passes {
+            canvas_png_save(canvas, "./out.png");
+        }
+    };
+    let canvas = crate::parallel::render_progressive_parallel(
+        scene.camera,
+        scene.world,
+        &scene.settings,
+        write_pass,
+    );
     write_to_file("./out.ppm", canvas.canvas_to_ppm());
     canvas_png_save(&canvas, "./out.png");
 }
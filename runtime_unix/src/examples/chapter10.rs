@@ -12,6 +12,7 @@ use mathlib_renderer::{
         light::Light,
         material::Material,
         patterns::Pattern,
+        render::RenderSettings,
         world::World,
     },
 };
@@ -30,7 +31,7 @@ pub fn build_example() {
 
     // colored red sphere in the middle:
     let mut middle = Sphere::new();
-    middle.transformation = Matrix::translation_new(-0.5, 1.0, 0.5);
+    middle.set_transform(Matrix::translation_new(-0.5, 1.0, 0.5));
     middle.material = base_mat.clone();
     middle.material.color(Col::new(0.1, 1.0, 0.5));
     middle.material.diffuse = 0.7;
@@ -39,8 +40,8 @@ pub fn build_example() {
 
     // colored green halfsize sphere on the right:
     let mut right = Sphere::new();
-    right.transformation =
-        Matrix::translation_new(1.2, 0.5, 0.7) * Matrix::scaling_new(0.5, 0.5, 0.5);
+    right
+        .set_transform(Matrix::translation_new(1.2, 0.5, 0.7) * Matrix::scaling_new(0.5, 0.5, 0.5));
     right.material = base_mat.clone();
     right.material.color(Col::new(0.5, 1.0, 0.1));
     right.material.diffuse = 0.7;
@@ -49,8 +50,9 @@ pub fn build_example() {
 
     // colored smallest sphere to the left:
     let mut left = Sphere::new();
-    left.transformation =
-        Matrix::translation_new(-1.5, 1.77, -0.75) * Matrix::scaling_new(0.33, 0.33, 0.33);
+    left.set_transform(
+        Matrix::translation_new(-1.5, 1.77, -0.75) * Matrix::scaling_new(0.33, 0.33, 0.33),
+    );
     left.material = base_mat;
     left.material.color(Col::new(1.0, 0.8, 0.1));
     left.material.diffuse = 0.7;
@@ -71,7 +73,7 @@ pub fn build_example() {
         Vector::new(0.0, 1.0, 0.0),
     ));
 
-    let canvas = crate::parallel::render_parallel(camera, world);
+    let canvas = crate::parallel::render_parallel(camera, world, &RenderSettings::default());
     write_to_file("./out.ppm", canvas.canvas_to_ppm());
     canvas_png_save(&canvas, "./out.png");
 }
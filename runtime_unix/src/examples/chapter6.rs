@@ -56,7 +56,7 @@ pub fn manually_cast_rays_at_sphere_infront_canvas() {
                     &point,
                     &eye_v,
                     &normal_v,
-                    false,
+                    1.0,
                 );
                 *col = color_with_lighting;
             }
@@ -1,22 +1,98 @@
-// using rayon for multi threading the process
+// the tiles get rendered in parallel by the renderer (with its rayon feature)
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, Once,
+};
 
 use indicatif::{ProgressBar, ProgressStyle};
-use mathlib_renderer::visual::{camera::Camera, canvas::Canvas, world::World};
-use rayon::prelude::*;
-pub fn render_parallel(camera: Camera, world: World) -> Canvas {
-    let bar = ProgressBar::new((camera.height) as u64);
+use mathlib_renderer::visual::{
+    camera::Camera,
+    canvas::Canvas,
+    render::{Progress, RenderSettings, RenderToken, Renderer},
+    world::World,
+};
+
+/// shows the progress of the render with a progress bar in the terminal.
+/// - the token gets cancelled by ctrl-c
+/// - with progressive rendering the bar starts over for every pass
+struct BarProgress {
+    bar: ProgressBar,
+    token: Arc<RenderToken>,
+    passes: usize,
+    pass: AtomicUsize,
+}
+
+impl Progress for BarProgress {
+    fn start(&self, tiles: usize) {
+        self.token.start(tiles);
+        self.bar.set_length(tiles as u64);
+        self.bar.set_position(0);
+        let pass = self.pass.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.passes > 1 {
+            self.bar.set_message(format!("pass {pass}/{}", self.passes));
+        }
+    }
+
+    fn tile_done(&self) {
+        self.token.tile_done();
+        self.bar.inc(1);
+    }
+
+    fn finish(&self) {
+        if self.token.is_cancelled() {
+            self.bar.abandon_with_message("stopped early");
+        } else if self.pass.load(Ordering::Relaxed) >= self.passes {
+            self.bar.finish();
+        }
+    }
+
+    fn should_stop(&self) -> bool {
+        self.token.should_stop()
+    }
+}
+
+/// the render that ctrl-c stops. The handler can only be set once, so it looks in here.
+static CURRENT_RENDER: Mutex<Option<Arc<RenderToken>>> = Mutex::new(None);
+static SET_HANDLER: Once = Once::new();
+
+/// renders till done or till ctrl-c gets pressed. Then returns what got rendered so far.
+/// - pressing ctrl-c a second time (or while not rendering) exits right away
+pub fn render_parallel(camera: Camera, world: World, settings: &RenderSettings) -> Canvas {
+    render_progressive_parallel(camera, world, settings, |_, _| ())
+}
+
+/// like render_parallel, but on_pass gets the image so far after every progressive pass
+/// (see RenderSettings::passes)
+pub fn render_progressive_parallel(
+    camera: Camera,
+    world: World,
+    settings: &RenderSettings,
+    on_pass: impl FnMut(usize, &Canvas),
+) -> Canvas {
+    let bar = ProgressBar::new(0);
     bar.set_style(ProgressStyle::default_bar().template(
-        "[{elapsed} elapsed] [{eta} left] {wide_bar:.green/white} {percent}% [speed-in-rows: {bytes_per_sec}] [total-rows: {pos} rows] ").unwrap());
-
-    let mut canvas = Canvas::new(camera.width, camera.height);
-    canvas.arr.par_iter_mut().enumerate().for_each(|(y, row)| {
-        row.par_iter_mut().enumerate().for_each(|(x, col)| {
-            let ray = camera.ray_for_pixel(x, y);
-            let color = world.color_at(&ray, 4);
-            *col = color;
+        "[{elapsed} elapsed] [{eta} left] {wide_bar:.green/white} {percent}% [speed-in-tiles: {bytes_per_sec}] [total-tiles: {pos} tiles] {msg}").unwrap());
+
+    let token = Arc::new(RenderToken::new());
+    *CURRENT_RENDER.lock().unwrap() = Some(token.clone());
+    SET_HANDLER.call_once(|| {
+        let handler = ctrlc::set_handler(|| match CURRENT_RENDER.lock().unwrap().as_ref() {
+            Some(token) if !token.is_cancelled() => token.cancel(),
+            _ => std::process::exit(130),
         });
-        bar.inc(1);
+        if let Err(err) = handler {
+            eprintln!("ctrl-c will not stop the render: {err}");
+        }
     });
-    bar.finish();
+
+    let progress = BarProgress {
+        bar,
+        token,
+        passes: settings.passes.max(1),
+        pass: AtomicUsize::new(0),
+    };
+    let canvas = Renderer::new(&camera, &world, *settings).render_progressive(&progress, on_pass);
+    *CURRENT_RENDER.lock().unwrap() = None;
     canvas
 }
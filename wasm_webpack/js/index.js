@@ -7,8 +7,13 @@ setupPage();
 function setupPage() {
   const renderBtn = document.getElementById('render');
   renderBtn.addEventListener('click', handleRenderBtnClicked);
+  const stopBtn = document.getElementById('stop');
+  stopBtn.addEventListener('click', () => { stopRequested = true; });
 }
 
+// set by the stop button. The workers stop picking up new tiles, what is rendered so far stays.
+var stopRequested = false;
+
 //
 //                  Rendering
 //
@@ -22,56 +27,138 @@ async function handleRenderBtnClicked(){
   const ctx = canvas.getContext('2d');
   const yaml_str = document.getElementById('input_yaml').value;
 
-  let {width, height} = await parseYamlForSceneData();
+  let sceneData;
+  try {
+    sceneData = await parseYamlForSceneData();
+  } catch (err) {
+    // broken scene, the message says what (and where in the yaml) went wrong
+    time_result.textContent = `could not load the scene: ${err.message}`;
+    return;
+  }
+  let {width, height, tileSize, passes} = sceneData;
   canvas.width = width;
   canvas.height = height;
 
   // start the rendering
+  stopRequested = false;
   toggleWorkInProgress();
-  await startParallelRendering(ctx, yaml_str, height);
+  const tiles = splitIntoTiles(width, height, tileSize);
+  const done = await startParallelRendering(ctx, yaml_str, tiles, passes, width, height);
   toggleWorkInProgress()
 
   // display timer
   const time_ms = new Date().getTime() - start_time.getTime();
-  time_result.innerHTML = `it took ${Math.round(time_ms/1000)}s to render`;
+  const total = tiles.length * passes;
+  time_result.innerHTML = done < total
+    ? `stopped after ${Math.round(time_ms/1000)}s with ${done}/${total} tiles rendered`
+    : `it took ${Math.round(time_ms/1000)}s to render`;
 }
 
 
 
-// Parse in yaml in wasm one time, to get width and height.
-// We could also check for (syntax-)errors here. For better error-feedback.
+// Parse in yaml in wasm one time, to get width, height and the tile size.
+// Throws the error of the WasmRenderer if the scene is broken.
 async function parseYamlForSceneData() {
   const yaml_str = document.getElementById('input_yaml').value;
   const worker = new Worker('./worker.js');
   const renderer = Comlink.wrap(worker);
-  await renderer.init({ start: 0, end: 0, yaml_str: yaml_str });
-  return await renderer.getWidthHeight();
+  try {
+    await renderer.init({ yaml_str: yaml_str });
+    return await renderer.getSceneData();
+  } finally {
+    worker.terminate();
+  }
+}
+
+// same order as the tiles of the rust Renderer: row by row, smaller tiles at the right/bottom edge
+function splitIntoTiles(width, height, tileSize) {
+  let tiles = [];
+  for (let y0 = 0; y0 < height; y0 += tileSize) {
+    for (let x0 = 0; x0 < width; x0 += tileSize) {
+      tiles.push({
+        x0: x0,
+        y0: y0,
+        width: Math.min(tileSize, width - x0),
+        height: Math.min(tileSize, height - y0),
+      });
+    }
+  }
+  return tiles;
 }
 
-async function startParallelRendering(ctx, yaml_str, sceneHeight) {
-  async function spawnRenderer(start, end) {
+// returns how many tiles got rendered (less than all of them, if it got stopped)
+// - progressive rendering: all tiles get rendered once per pass. Each finished tile gets averaged
+//   with its earlier passes, so the whole image is visible after the first pass and gets better
+async function startParallelRendering(ctx, yaml_str, tiles, passes, width, height) {
+  // every worker takes the next tile from the shared queue when it is done with its last one.
+  // So a worker that got stuck with the expensive parts of the image does not hold up the others.
+  // The queue goes trough all tiles of a pass before starting with the next pass.
+  const jobs = [];
+  for (let pass = 0; pass < passes; pass++) {
+    tiles.forEach((tile, index) => jobs.push({ tile: tile, index: index, pass: pass }));
+  }
+  // accumulation buffer: sum of all passes so far, rgb for each pixel. Like the rust Accumulator it
+  // sums up the unclamped colors, so browser and cli converge to the same image.
+  const sums = new Float64Array(width * height * 3);
+  // passes might finish out of order, so we count them for every tile
+  const tilePasses = new Array(tiles.length).fill(0);
+  let next = 0;
+  let done = 0;
+  async function spawnRenderer() {
     const worker = new Worker('./worker.js');
     const renderer = Comlink.wrap(worker);
     
-    await renderer.init({ start: start, end: end, yaml_str: yaml_str });
-
-    console.log("starting worker for ", start, " to ", end);
-    let result;
-    while ((result = await renderer.renderNext()) !== false) {
-      ctx.putImageData(result.imgData, 0, result.y);
+    await renderer.init({ yaml_str: yaml_str });
+
+    while (!stopRequested && next < jobs.length) {
+      const { tile, index, pass } = jobs[next++];
+      const result = await renderer.renderTile(tile, pass);
+      const imgData = accumulate(sums, width, result, ++tilePasses[index]);
+      ctx.putImageData(imgData, result.x0, result.y0);
+      done++;
+      time_result.innerHTML = `rendered ${done}/${jobs.length} tiles`;
     }
+    worker.terminate();
   }
 
   // browsers report untrue cpu-count, so we add some on top.
   const workerCount = getAvailableCores();
-  const perWorker = sceneHeight / workerCount;
+  console.log(`rendering ${tiles.length} tiles with ${workerCount} workers`);
   let tasks = [];
 
   for (let i = 0; i < workerCount; i++) {
-    tasks.push(spawnRenderer(i * perWorker, (i + 1) * perWorker));
+    tasks.push(spawnRenderer());
   }
 
   await Promise.all(tasks);
+  return done;
+}
+
+// adds the rendered tile to the sums and returns the average of all passes of the tile so far
+// (the same math as Accumulator::image in rust)
+function accumulate(sums, width, { x0, y0, width: tileWidth, height: tileHeight, colors }, passCount) {
+  const imgData = new ImageData(tileWidth, tileHeight);
+  const data = imgData.data;
+  const factor = 1 / passCount;
+  for (let y = 0; y < tileHeight; y++) {
+    for (let x = 0; x < tileWidth; x++) {
+      const i = (y * tileWidth + x) * 3;
+      const j = ((y0 + y) * width + x0 + x) * 3;
+      const k = (y * tileWidth + x) * 4;
+      for (let c = 0; c < 3; c++) {
+        sums[j + c] += colors[i + c];
+        data[k + c] = base255(sums[j + c] * factor);
+      }
+      data[k + 3] = 255; // transparency
+    }
+  }
+  return imgData;
+}
+
+// same as color::base_255 in rust: clamps to 0..1 and scales to a byte
+function base255(value) {
+  if (value < 0) return 0;
+  return Math.min(255, Math.floor(value * 256));
 }
 
 function getAvailableCores() {
@@ -138,6 +225,7 @@ function toggleWorkInProgress() {
     const btn = document.getElementById("render");
     btn.disabled = true;
     btn.classList.add("disabled");
+    document.getElementById("stop").disabled = false;
     const box = document.getElementById("checkbox_onload");
     box.disabled = true;
     state = box.checked ? "wasChecked" : "notChecked"
@@ -146,6 +234,7 @@ function toggleWorkInProgress() {
     const btn = document.getElementById("render");
     btn.disabled = false;
     btn.classList.remove("disabled");
+    document.getElementById("stop").disabled = true;
     const box = document.getElementById("checkbox_onload");
     box.disabled = false;
     box.checked = state === "wasChecked" ? true : false;
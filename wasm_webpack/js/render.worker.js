@@ -5,27 +5,28 @@ class RendererWorker{
   // sets up wasm for this worker and current state.
   // Web workers cant really share a wasm-instance. So they all start their own here. Only the
   // Wasm-Object: 'Renderer' need's to be saved by reference to call into this wasm instance
-  async init({start: start, end: end, yaml_str: yaml_str}) {
+  async init({yaml_str: yaml_str}) {
     await import("../pkg").then((wasm) => {
       wasm.main_js(); // initialize rust-panic -> console.error pipline so errors are passed down.
  
-    this.wasmRenderer = new wasm.WasmRenderer(yaml_str);
-      this.end = end;
-      this.y = start;
+      this.wasmRenderer = new wasm.WasmRenderer(yaml_str);
     });
   }
 
-  async renderNext() {
-    if (this.y >= this.end) {
-      return false;
-    }
-
-    const imgData = this.wasmRenderer.row_to_image_pixels(this.y);
-    return { y: this.y++, imgData: imgData };
+  // renders one tile of the given progressive pass. colors are the unclamped r, g, b floats of
+  // each pixel, so the passes can be averaged before they get turned into bytes.
+  async renderTile({x0: x0, y0: y0, width: width, height: height}, pass) {
+    const colors = this.wasmRenderer.region_colors(x0, y0, width, height, pass);
+    return { x0: x0, y0: y0, width: width, height: height, colors: colors };
   }
 
-  async getWidthHeight() {
-    return { width: this.wasmRenderer.width, height: this.wasmRenderer.height }
+  async getSceneData() {
+    return {
+      width: this.wasmRenderer.width,
+      height: this.wasmRenderer.height,
+      tileSize: this.wasmRenderer.tile_size,
+      passes: this.wasmRenderer.passes,
+    }
   }
 }
 
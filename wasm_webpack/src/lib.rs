@@ -1,11 +1,8 @@
 
-use mathlib_renderer::visual::color;
+use mathlib_renderer::visual::render::Renderer;
 use parselib_yaml::yaml::SceneToRun;
 use wasm_bindgen::prelude::*;
 
-use wasm_bindgen::Clamped;
-use web_sys::ImageData;
-
 // when the wee_alloc feature flag is set, we use this as global allocator
 #[cfg(feature = "wee_alloc")]
 #[global_allocator]
@@ -29,36 +26,32 @@ pub struct WasmRenderer {
   scene: SceneToRun,
   pub height: u32,
   pub width: u32,
+  /// the js-side splits the image into tiles of this size and hands those out to the workers
+  pub tile_size: u32,
+  /// progressive rendering: how often the js-side renders all tiles and averages them
+  pub passes: u32,
 }
 #[wasm_bindgen]
 impl WasmRenderer {
+  /// throws a js Error with a readable message (and where in the yaml) if the scene is broken
   #[wasm_bindgen(constructor)]
-  pub fn new(yaml_str: &str) -> Self {
-    let scene = SceneToRun::new_from_yaml(yaml_str);
+  pub fn new(yaml_str: &str) -> Result<WasmRenderer, JsValue> {
+    let scene = SceneToRun::new_from_yaml(yaml_str)
+      .map_err(|err| js_sys::Error::new(&err.to_string()))?;
     let height = scene.camera.height as u32;
     let width = scene.camera.width as u32;
-    Self { scene, height, width }
-  }
-
-  fn get_row_pixels(&self, y: usize) -> Vec<u8> {
-    let mut raw_pixels: Vec<u8> = 
-      Vec::with_capacity(self.width as usize * 4);
-
-    for x in 0..self.scene.camera.width {
-      let ray = self.scene.camera.ray_for_pixel(x, y);
-      let color = self.scene.world.color_at(&ray, 2);
-      raw_pixels.push(color::base_255(color.r));
-      raw_pixels.push(color::base_255(color.g));
-      raw_pixels.push(color::base_255(color.b));
-      raw_pixels.push(255); // transparency
-    }
-
-    raw_pixels
+    let tile_size = scene.settings.tile_size as u32;
+    let passes = scene.settings.passes as u32;
+    Ok(Self { scene, height, width, tile_size, passes })
   }
 
-  pub fn row_to_image_pixels(&self, y:usize) -> Result<ImageData, JsValue> {
-    let data = self.get_row_pixels(y);
-    ImageData::new_with_u8_clamped_array_and_sh(Clamped(&data), self.width, 1)
+  /// the colors of the region as they are (not clamped to 0..1 yet), r, g, b for each pixel, row by row.
+  /// Progressive rendering sums these up on the js-side, like the Accumulator does, and only turns
+  /// the average into bytes. So the browser ends up with the same image as the cli.
+  pub fn region_colors(&self, x0: usize, y0: usize, w: usize, h: usize, pass: usize) -> Vec<f64> {
+    let renderer = Renderer::new(&self.scene.camera, &self.scene.world, self.scene.settings);
+    let block = renderer.for_pass(pass).render_region(x0, y0, w, h);
+    block.arr.iter().flatten().flat_map(|color| [color.r, color.g, color.b]).collect()
   }
 
   // /// directly draws to the Javascript-canvas element's memory